@@ -1,10 +1,21 @@
 #![windows_subsystem = "windows"]
 
 mod state {
+    #[allow(clippy::module_inception)]
     pub mod state;
     pub mod update;
     pub mod client_manager;
+    pub mod control;
+    pub mod access_manager;
+    pub mod audit_log;
     pub mod file_manager;
+    pub mod invite_manager;
+    pub mod hooks;
+    pub mod network_manager;
+    pub mod notifications;
+    pub mod paths;
+    pub mod profiles;
+    pub mod single_instance;
     pub mod subscriptions;
     mod theme_selector;
 }
@@ -16,20 +27,46 @@ mod views {
     mod download_pane;
     mod footer_pane;
     mod connection_info_pane;
+    mod approval_pane;
+    mod settings_pane;
+    mod history_pane;
+    mod throughput_chart;
     pub mod language;
 }
 mod server {
     pub mod router;
     pub mod webpage_service;
+    pub mod rate_limiter;
+    pub mod mdns;
+    mod client_ip;
+    pub mod access_log;
+    mod session;
+    pub mod metrics;
+    pub mod connection_tracker;
+    pub mod event_bus;
     mod download_service;
     mod counting_stream;
+    mod file_stream;
+    pub mod benchmark;
 }
 
-use state::{file_manager::FileManager, subscriptions::subscription, update::update};
+use state::{control, file_manager::FileManager, single_instance::{cli_paths, forward_to_running, release}, state::State, subscriptions::subscription, update::{restore_shared_files, update, Message}};
 use views::root_view::view;
 use iced::Size;
 
 fn main() -> iced::Result { 
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.get(1).is_some_and(|arg| arg == "ctl") {
+        #[cfg(target_os = "windows")]
+        control::attach_console();
+        std::process::exit(control::run_cli(&args[2..]));
+    }
+
+    let paths = cli_paths();
+    if forward_to_running(&paths) {
+        return Ok(());
+    }
+
     let _ = iced::application("Fileshare", update, view)
         .subscription(subscription)
         .window(iced::window::Settings {
@@ -40,9 +77,19 @@ fn main() -> iced::Result {
             ..iced::window::Settings::default()
         })
         .theme(|state| state.theme.get())
-        .run();
+        .run_with(|| {
+            let mut state = State::default();
+            let entries = std::mem::take(&mut state.pending_restore);
+            let restored = restore_shared_files(&mut state, entries);
+            let opened = match paths.is_empty() {
+                true => iced::Task::none(),
+                false => update(&mut state, Message::OpenPaths(paths)),
+            };
+            (state, iced::Task::batch([restored, opened]))
+        });
 
     let _ = std::fs::remove_dir_all(FileManager::temp_dir());
+    release();
 
     Ok(())
 }
@@ -0,0 +1,235 @@
+use std::{fs::{File, OpenOptions}, io::Write, net::IpAddr, path::PathBuf, sync::{Arc, Mutex, RwLock}};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use futures::StreamExt;
+use warp::{http::{Method, StatusCode, Version}, hyper::Body, path::FullPath, reject::Rejection, reply::{Reply, Response}, Filter};
+
+use crate::state::access_manager::AccessManager;
+
+use super::client_ip::client_ip;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogRotation {
+    #[default]
+    Daily,
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    pub rotation: LogRotation,
+    pub max_size_mb: usize,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rotation: LogRotation::Daily,
+            max_size_mb: 10,
+        }
+    }
+}
+
+pub struct RequestInfo {
+    ip: Option<IpAddr>,
+    method: Method,
+    path: FullPath,
+    query: String,
+    version: Option<Version>,
+    user_agent: Option<String>,
+    referer: Option<String>,
+}
+
+struct LogFile {
+    file: File,
+    size: u64,
+    date: NaiveDate,
+}
+
+#[derive(Clone)]
+pub struct AccessLog {
+    path: PathBuf,
+    config: Arc<RwLock<AccessLogConfig>>,
+    file: Arc<Mutex<Option<LogFile>>>,
+}
+
+impl AccessLog {
+    pub fn new(path: PathBuf, config: AccessLogConfig) -> Self {
+        Self {
+            path,
+            config: Arc::new(RwLock::new(config)),
+            file: Arc::default(),
+        }
+    }
+
+    pub fn config(&self) -> AccessLogConfig {
+        *self.config.read().unwrap()
+    }
+
+    pub fn set_config(&self, config: AccessLogConfig) {
+        *self.config.write().unwrap() = config;
+        if !config.enabled {
+            *self.file.lock().unwrap() = None;
+        }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Logs the request once its body is done, so the entry has the bytes that were actually sent.
+    pub fn log(&self, info: RequestInfo, reply: impl Reply) -> Response {
+        let response = reply.into_response();
+        if !self.config().enabled {
+            return response;
+        }
+
+        let mut entry = PendingEntry {
+            log: self.clone(),
+            info,
+            status: response.status(),
+            time: Local::now(),
+            sent: 0,
+        };
+        response.map(|body| Body::wrap_stream(body.map(move |chunk| {
+            entry.count(&chunk);
+            chunk
+        })))
+    }
+
+    fn write(&self, config: &AccessLogConfig, now: DateTime<Local>, line: &[u8]) {
+        let mut file = self.file.lock().unwrap();
+
+        let rotate = file.as_ref().is_some_and(|file| match config.rotation {
+            LogRotation::Daily => file.date != now.date_naive(),
+            LogRotation::Size => file.size + line.len() as u64 > (config.max_size_mb.max(1) as u64) << 20,
+        });
+        if rotate {
+            let date = file.take().map(|file| file.date).unwrap_or_else(|| now.date_naive());
+            let suffix = match config.rotation {
+                LogRotation::Daily => date.format("%Y-%m-%d").to_string(),
+                LogRotation::Size => now.format("%Y-%m-%d-%H%M%S").to_string(),
+            };
+            let mut rotated = self.path.clone().into_os_string();
+            rotated.push(format!(".{}", suffix));
+            let _ = std::fs::rename(&self.path, rotated);
+        }
+
+        if file.is_none() {
+            *file = self.open();
+        }
+        if let Some(file) = file.as_mut()
+            && file.file.write_all(line).is_ok() {
+            file.size += line.len() as u64;
+        }
+    }
+
+    fn open(&self) -> Option<LogFile> {
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&self.path).ok()?;
+        let metadata = file.metadata().ok()?;
+        let date = metadata.modified()
+            .map(|modified| DateTime::<Local>::from(modified).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+        Some(LogFile { file, size: metadata.len(), date })
+    }
+}
+
+struct PendingEntry {
+    log: AccessLog,
+    info: RequestInfo,
+    status: StatusCode,
+    time: DateTime<Local>,
+    sent: u64,
+}
+
+impl PendingEntry {
+    fn count<E>(&mut self, chunk: &Result<bytes::Bytes, E>) {
+        if let Ok(chunk) = chunk {
+            self.sent += chunk.len() as u64;
+        }
+    }
+}
+
+impl Drop for PendingEntry {
+    fn drop(&mut self) {
+        let config = self.log.config();
+        if !config.enabled {
+            return;
+        }
+        let info = &self.info;
+        let line = format!("{} - - [{}] \"{} {}{} {}\" {} {} \"{}\" \"{}\"\n",
+            info.ip.map(|ip| ip.to_string()).unwrap_or_else(|| "-".to_string()),
+            self.time.format("%d/%b/%Y:%H:%M:%S %z"),
+            info.method,
+            info.path.as_str(),
+            redact_invite(&info.query),
+            info.version.map(|version| format!("{:?}", version)).unwrap_or_else(|| "HTTP/1.1".to_string()),
+            self.status.as_u16(),
+            Some(self.sent).filter(|sent| *sent > 0).map(|sent| sent.to_string()).unwrap_or_else(|| "-".to_string()),
+            escape(info.referer.as_deref().map(redact_invite).as_deref()),
+            escape(info.user_agent.as_deref()),
+        );
+        self.log.write(&config, Local::now(), line.as_bytes());
+    }
+}
+
+pub fn request_info(
+    access: AccessManager,
+) -> impl Filter<Extract = (RequestInfo,), Error = Rejection> + Clone {
+    client_ip(access)
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::query::raw().map(|query| format!("?{}", query)).or(warp::any().map(String::new)).unify())
+        .and(warp::ext::optional::<Version>())
+        .and(warp::header::optional::<String>("user-agent"))
+        .and(warp::header::optional::<String>("referer"))
+        .map(|ip, method, path, query, version, user_agent, referer| {
+            RequestInfo { ip, method, path, query, version, user_agent, referer }
+        })
+}
+
+/// Invite tokens grant access to the share, so they must not end up in a log file.
+fn redact_invite(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let query = query.split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some(("invite", _)) => "invite=REDACTED",
+            _ => pair,
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}?{}", base, query)
+}
+
+fn escape(field: Option<&str>) -> String {
+    match field {
+        Some(field) => field.replace('\\', "\\\\").replace('"', "\\\""),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invite_tokens_are_redacted() {
+        assert_eq!(redact_invite("?invite=secret"), "?invite=REDACTED");
+        assert_eq!(redact_invite("?a=1&invite=secret&b=2"), "?a=1&invite=REDACTED&b=2");
+        assert_eq!(redact_invite("http://host/index?invite=secret"), "http://host/index?invite=REDACTED");
+    }
+
+    #[test]
+    fn other_queries_are_kept() {
+        assert_eq!(redact_invite(""), "");
+        assert_eq!(redact_invite("http://host/index"), "http://host/index");
+        assert_eq!(redact_invite("?invited=1&x"), "?invited=1&x");
+    }
+}
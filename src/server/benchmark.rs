@@ -0,0 +1,76 @@
+use std::{convert::Infallible, net::{Ipv4Addr, SocketAddr}, path::PathBuf, sync::Arc, time::Instant};
+use futures::future::{select, Either};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpStream, sync::Semaphore};
+use tokio_util::sync::CancellationToken;
+use warp::hyper::{service::{make_service_fn, service_fn}, Body, Response, Server};
+
+use crate::state::{client_manager::ClientId, file_manager::FileManager};
+
+use super::{counting_stream::CountingStream, event_bus::ProgressRegistry, file_stream::file_stream};
+
+const BENCHMARK_SIZE: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub throughput: usize,
+}
+
+pub async fn run_benchmark() -> Result<BenchmarkResult, String> {
+    let path = FileManager::temp_dir().join("benchmark.bin");
+    std::fs::create_dir_all(FileManager::temp_dir()).map_err(|err| err.to_string())?;
+    std::fs::File::create(&path)
+        .and_then(|file| file.set_len(BENCHMARK_SIZE))
+        .map_err(|err| err.to_string())?;
+
+    let throughput = measure(path.clone()).await;
+    let _ = std::fs::remove_file(&path);
+
+    Ok(BenchmarkResult { throughput: throughput? })
+}
+
+async fn measure(path: PathBuf) -> Result<usize, String> {
+    let make_service = make_service_fn(move |_| {
+        let path = path.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |_| {
+                let path = path.clone();
+                async move {
+                    let (file, _) = file_stream(&path).await?;
+                    let (tx, _) = futures::channel::mpsc::unbounded();
+                    let permit = Arc::new(Semaphore::new(1)).try_acquire_owned().unwrap();
+                    let client = ClientId { ip: Ipv4Addr::LOCALHOST.into(), invite: None, session: String::new() };
+                    let progress = ProgressRegistry::default().counter(&client, 0);
+                    let stream = CountingStream::new(file, tx, 0, client, progress, permit, CancellationToken::new());
+                    Ok::<_, std::io::Error>(Response::new(Body::wrap_stream(stream)))
+                }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+        .map_err(|err| err.to_string())?
+        .serve(make_service);
+    let addr = server.local_addr();
+
+    match select(Box::pin(server), Box::pin(download(addr))).await {
+        Either::Left((result, _)) => Err(result.err().map(|err| err.to_string()).unwrap_or_default()),
+        Either::Right((result, _)) => result.map_err(|err| err.to_string()),
+    }
+}
+
+async fn download(addr: SocketAddr) -> std::io::Result<usize> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await?;
+
+    let start = Instant::now();
+    let mut buffer = vec![0; 1024 * 1024];
+    let mut received = 0;
+    loop {
+        match stream.read(&mut buffer).await? {
+            0 => break,
+            n => received += n,
+        }
+    }
+
+    Ok((received as f64 / start.elapsed().as_secs_f64()) as usize)
+}
@@ -0,0 +1,104 @@
+use std::net::{IpAddr, SocketAddr};
+use warp::{reject::Rejection, Filter};
+
+use crate::state::access_manager::AccessManager;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteAddr(pub SocketAddr);
+
+pub fn client_ip(
+    access: AccessManager,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = Rejection> + Clone {
+    warp::ext::optional::<RemoteAddr>()
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .and(warp::header::optional::<String>("forwarded"))
+        .map(move |addr: Option<RemoteAddr>, x_forwarded_for: Option<String>, forwarded: Option<String>| {
+            addr.map(|RemoteAddr(addr)| forwarded_ip(addr.ip().to_canonical(), x_forwarded_for, forwarded, &access))
+        })
+}
+
+fn forwarded_ip(remote: IpAddr, x_forwarded_for: Option<String>, forwarded: Option<String>, access: &AccessManager) -> IpAddr {
+    if !access.is_proxy(remote) {
+        return remote;
+    }
+
+    let chain: Vec<IpAddr> = match (forwarded, x_forwarded_for) {
+        (Some(forwarded), _) => forwarded
+            .split(',')
+            .filter_map(|element| element
+                .split(';')
+                .find_map(|pair| pair.trim().strip_prefix("for=").or_else(|| pair.trim().strip_prefix("For=")))
+                .and_then(parse_node))
+            .collect(),
+        (None, Some(x_forwarded_for)) => x_forwarded_for
+            .split(',')
+            .filter_map(parse_node)
+            .collect(),
+        (None, None) => Vec::new(),
+    };
+
+    chain.into_iter()
+        .rev()
+        .map(|ip| ip.to_canonical())
+        .find(|ip| !access.is_proxy(*ip))
+        .unwrap_or(remote)
+}
+
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    node.parse::<IpAddr>().ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| node.strip_prefix('[')
+            .and_then(|node| node.split(']').next())
+            .and_then(|node| node.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::access_manager::AccessRules;
+    use super::*;
+
+    fn proxy_access() -> AccessManager {
+        let rules = AccessRules { proxies: vec!["10.0.0.0/8".parse().unwrap()], ..AccessRules::default() };
+        AccessManager::new(rules, false)
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn nodes_are_parsed_with_and_without_ports() {
+        assert_eq!(parse_node(" 192.168.1.2 "), Some(ip("192.168.1.2")));
+        assert_eq!(parse_node("192.168.1.2:8080"), Some(ip("192.168.1.2")));
+        assert_eq!(parse_node("\"[2001:db8::1]:4711\""), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("\"[2001:db8::1]\""), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+
+    #[test]
+    fn headers_are_ignored_from_untrusted_peers() {
+        let forwarded = Some("1.2.3.4".to_string());
+        assert_eq!(forwarded_ip(ip("192.168.1.2"), forwarded.clone(), forwarded, &proxy_access()), ip("192.168.1.2"));
+    }
+
+    #[test]
+    fn the_last_untrusted_hop_is_the_client() {
+        let x_forwarded_for = Some("1.2.3.4, 5.6.7.8, 10.0.0.2".to_string());
+        assert_eq!(forwarded_ip(ip("10.0.0.1"), x_forwarded_for, None, &proxy_access()), ip("5.6.7.8"));
+    }
+
+    #[test]
+    fn forwarded_takes_precedence() {
+        let forwarded = Some("for=1.2.3.4;proto=http, For=\"[2001:db8::1]:80\"".to_string());
+        let x_forwarded_for = Some("5.6.7.8".to_string());
+        assert_eq!(forwarded_ip(ip("10.0.0.1"), x_forwarded_for, forwarded, &proxy_access()), ip("2001:db8::1"));
+    }
+
+    #[test]
+    fn a_chain_of_proxies_falls_back_to_the_peer() {
+        let x_forwarded_for = Some("10.0.0.3, garbage".to_string());
+        assert_eq!(forwarded_ip(ip("10.0.0.1"), x_forwarded_for, None, &proxy_access()), ip("10.0.0.1"));
+    }
+}
@@ -0,0 +1,38 @@
+use std::{collections::HashMap, net::IpAddr, sync::{Arc, Mutex}};
+
+#[derive(Clone, Default)]
+pub struct ConnectionTracker {
+    connections: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+pub struct ConnectionGuard {
+    tracker: ConnectionTracker,
+    ip: IpAddr,
+}
+
+impl ConnectionTracker {
+    pub fn open(&self, ip: IpAddr) -> ConnectionGuard {
+        *self.connections.lock().unwrap().entry(ip).or_default() += 1;
+        ConnectionGuard { tracker: self.clone(), ip }
+    }
+
+    pub fn count(&self, ip: IpAddr) -> usize {
+        self.connections.lock().unwrap().get(&ip).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> usize {
+        self.connections.lock().unwrap().values().sum()
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections = self.tracker.connections.lock().unwrap();
+        if let Some(count) = connections.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                connections.remove(&self.ip);
+            }
+        }
+    }
+}
@@ -1,23 +1,28 @@
-use std::net::IpAddr;
-use futures::{channel::mpsc::Sender, stream::Stream};
+use futures::stream::Stream;
 use std::pin::Pin;
+use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
 use std::task::{Context, Poll};
+use tokio_util::sync::CancellationToken;
 
-use crate::state::update::ServerMessage;
+use crate::state::{client_manager::ClientId, update::ServerMessage};
+
+use super::event_bus::EventSender;
 
 pub struct CountingStream<S> {
     inner: S,
-    tx: Sender<ServerMessage>,
+    tx: EventSender,
     index: usize,
-    ip: IpAddr,
-    counter: usize,
-    last_send_time: tokio::time::Instant,
+    client: ClientId,
+    progress: Arc<AtomicUsize>,
+    sent: usize,
+    done: bool,
     _permit: tokio::sync::OwnedSemaphorePermit,
+    cancel: CancellationToken,
 }
 
 impl<S> CountingStream<S> {
-    pub fn new(inner: S, tx: Sender<ServerMessage>, index: usize, ip: IpAddr, permit: tokio::sync::OwnedSemaphorePermit) -> CountingStream<S> {
-        CountingStream { inner, tx, index, ip, counter: 0, last_send_time: tokio::time::Instant::now(), _permit: permit }
+    pub fn new(inner: S, tx: EventSender, index: usize, client: ClientId, progress: Arc<AtomicUsize>, permit: tokio::sync::OwnedSemaphorePermit, cancel: CancellationToken) -> CountingStream<S> {
+        CountingStream { inner, tx, index, client, progress, sent: 0, done: false, _permit: permit, cancel }
     }
 }
 
@@ -28,29 +33,34 @@ where
     type Item = Result<bytes::Bytes, std::io::Error>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.cancel.is_cancelled() {
+            return Poll::Ready(Some(Err(std::io::Error::from(std::io::ErrorKind::ConnectionAborted))));
+        }
         match Pin::new(&mut self.inner).poll_next(cx) {
             Poll::Ready(None) => {
                 let index = self.index;
-                let ip = self.ip;
-                let counter = self.counter;
-                let _ = self.tx.try_send(ServerMessage::DownloadActive { ip, num_bytes: counter });
-                let _ = self.tx.try_send(ServerMessage::Downloaded { index, ip });
+                let client = self.client.clone();
+                let bytes = self.sent;
+                let _ = self.tx.unbounded_send(ServerMessage::Downloaded { index, client, bytes });
+                self.done = true;
                 Poll::Ready(None)
             }
             Poll::Ready(Some(Err(_))) => Poll::Ready(None),
             Poll::Ready(Some(data)) => {
                 let size = data.as_ref().map(|b| b.len()).unwrap_or(0);
-                self.counter += size;
-                if self.last_send_time.elapsed().as_millis() > 250 {
-                    let ip = self.ip;
-                    let counter = self.counter;
-                    let _ = self.tx.try_send(ServerMessage::DownloadActive { ip, num_bytes: counter });
-                    self.counter = 0;
-                    self.last_send_time = tokio::time::Instant::now();
-                }
+                self.progress.fetch_add(size, Ordering::Relaxed);
+                self.sent += size;
                 Poll::Ready(Some(data))
             }
             p @ Poll::Pending => p,
         }
     }
 }
+
+impl<S> Drop for CountingStream<S> {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.tx.unbounded_send(ServerMessage::DownloadAborted { index: self.index, client: self.client.clone(), bytes: self.sent });
+        }
+    }
+}
@@ -1,45 +1,50 @@
-use std::{collections::HashMap, net::IpAddr, sync::{Arc, RwLock}};
+use std::{collections::HashMap, sync::{Arc, RwLock}};
 use warp::{http::header, reply::Response, Filter};
-use tokio::{fs::File, sync::Mutex};
-use tokio_util::io::ReaderStream;
 use warp::hyper::Body;
-use futures::channel::mpsc::Sender;
-use crate::{state::file_manager, state::update::ServerMessage};
+use crate::{state::{access_manager::AccessManager, client_manager::ClientId, file_manager, invite_manager::{Invite, InviteManager}}, state::update::ServerMessage};
 
-use super::counting_stream::CountingStream;
-
-const PERMITS_PER_CLIENT: usize = 5;
+use super::{client_ip::client_ip, counting_stream::CountingStream, event_bus::{EventSender, ProgressRegistry}, file_stream::file_stream, rate_limiter::RateLimiter, router::invite_access, session::{session, Session}};
 
 pub fn download_route(
     files: Arc<RwLock<HashMap<usize, file_manager::FileInfo>>>, 
-    tx: Sender<ServerMessage>, 
-    semaphor: Arc<Mutex<HashMap<IpAddr, Arc<tokio::sync::Semaphore>>>>
+    invites: InviteManager,
+    access: AccessManager,
+    limiter: RateLimiter,
+    progress: ProgressRegistry,
+    tx: EventSender, 
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("download" / usize / usize)
-        .and(warp::addr::remote())
-        .and_then(move |index, is_single, addr: Option<std::net::SocketAddr>| {
-            let mut tx = tx.clone();
+        .and(client_ip(access.clone()))
+        .and(invite_access(invites))
+        .and(session())
+        .and_then(move |index, is_single, ip: Option<std::net::IpAddr>, invite: Option<Invite>, session: Session| {
+            let tx = tx.clone();
+            let progress = progress.clone();
             let files = files.clone();
-            let semaphor = semaphor.clone();
+            let limiter = limiter.clone();
+            let access = access.clone();
             async move {
-                if is_single == 1 {
-                    tx.try_send(ServerMessage::DownloadRequest { index, ip: addr.unwrap().ip() })
-                        .map_err(|_| warp::reject::reject())?;
-                }
                 let file_info: file_manager::FileInfo = files.read()
                     .unwrap()
                     .get(&index)
                     .cloned()
-                    .ok_or_else(|| warp::reject::not_found())?;
-                let file = File::open(&file_info.path)
+                    .ok_or_else(warp::reject::not_found)?;
+                if invite.as_ref().is_some_and(|invite| !invite.can_access(&file_info.path)) {
+                    return Err(warp::reject::not_found());
+                }
+                let client = ClientId { ip: ip.unwrap(), invite: invite.map(|invite| invite.token), session: session.id };
+                let permit = limiter.acquire_connection(client.ip)
+                    .await
+                    .map_err(warp::reject::custom)?;
+                if is_single == 1 {
+                    let _ = tx.unbounded_send(ServerMessage::DownloadRequest { index, client: client.clone() });
+                }
+                let (file, size) = file_stream(&file_info.path)
                     .await
                     .map_err(|_| warp::reject::not_found())?;
-                let semaphor = semaphor.lock().await
-                    .entry(addr.unwrap().ip())
-                    .or_insert_with(|| Arc::new(tokio::sync::Semaphore::new(PERMITS_PER_CLIENT)))
-                    .clone();
-                let permit = semaphor.acquire_owned().await.unwrap();
-                let stream = CountingStream::new(ReaderStream::new(file), tx, index, addr.unwrap().ip(), permit);
+                let cancel = access.connection_token(client.ip);
+                let counter = progress.counter(&client, index);
+                let stream = CountingStream::new(file, tx, index, client, counter, permit, cancel);
                 let body = Body::wrap_stream(stream);
                 let response = warp::reply::with_header(
                     Response::new(body), 
@@ -47,20 +52,26 @@ pub fn download_route(
                     format!("attachment; filename=\"{}\"", 
                     file_info.path.file_name().unwrap().to_str().unwrap()
                 ));
+                let response = warp::reply::with_header(response, header::CONTENT_LENGTH, size);
                 Ok::<_, warp::Rejection>(response)
             }
     })
 }
 
 pub fn download_all_route(
-    tx: Sender<ServerMessage>, 
+    invites: InviteManager,
+    access: AccessManager,
+    tx: EventSender, 
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("download-all")
-        .and(warp::addr::remote())
-        .and_then(move |ip: Option<std::net::SocketAddr>| {
-            let mut tx = tx.clone();
+        .and(client_ip(access.clone()))
+        .and(invite_access(invites))
+        .and(session())
+        .and_then(move |ip: Option<std::net::IpAddr>, invite: Option<Invite>, session: Session| {
+            let tx = tx.clone();
             async move {
-                let _ = tx.try_send(ServerMessage::DownloadAllRequest { ip: ip.unwrap().ip() });
+                let client = ClientId { ip: ip.unwrap(), invite: invite.map(|invite| invite.token), session: session.id };
+                let _ = tx.unbounded_send(ServerMessage::DownloadAllRequest { client });
                 Ok::<_, warp::Rejection>(warp::reply::with_status("Download started", warp::http::StatusCode::OK))
             }
         })
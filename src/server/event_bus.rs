@@ -0,0 +1,70 @@
+use std::{collections::HashMap, sync::{atomic::{AtomicUsize, Ordering}, Arc, RwLock}};
+use futures::channel::mpsc::UnboundedSender;
+
+use crate::state::{client_manager::ClientId, update::ServerMessage};
+
+pub type EventSender = UnboundedSender<ServerMessage>;
+
+type TransferKey = (ClientId, usize);
+
+#[derive(Clone, Default)]
+pub struct ProgressRegistry {
+    counters: Arc<RwLock<HashMap<TransferKey, Arc<AtomicUsize>>>>,
+}
+
+impl ProgressRegistry {
+    pub fn counter(&self, client: &ClientId, index: usize) -> Arc<AtomicUsize> {
+        let key = (client.clone(), index);
+        if let Some(counter) = self.counters.read().unwrap().get(&key) {
+            return counter.clone();
+        }
+        self.counters.write().unwrap()
+            .entry(key)
+            .or_default()
+            .clone()
+    }
+
+    pub fn drain(&self) -> Vec<(ClientId, usize, usize)> {
+        let mut drained = Vec::new();
+        self.counters.write().unwrap().retain(|(client, index), counter| {
+            // Checked before the swap: once no stream holds the counter, nothing can add bytes after it.
+            let finished = Arc::strong_count(counter) == 1;
+            if finished {
+                // Pairs with the release of the dropped `Arc`, so its last bytes are visible to the swap.
+                std::sync::atomic::fence(Ordering::Acquire);
+            }
+            let bytes = counter.swap(0, Ordering::Relaxed);
+            if bytes > 0 {
+                drained.push((client.clone(), *index, bytes));
+            }
+            !finished
+        });
+        drained
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.counters.read().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use super::*;
+
+    #[test]
+    fn finished_transfers_are_removed_after_their_last_drain() {
+        let registry = ProgressRegistry::default();
+        let client = ClientId { ip: Ipv4Addr::LOCALHOST.into(), invite: None, session: String::new() };
+        let counter = registry.counter(&client, 0);
+        counter.fetch_add(10, Ordering::Relaxed);
+        assert_eq!(registry.drain(), vec![(client.clone(), 0, 10)]);
+        assert_eq!(registry.len(), 1);
+
+        counter.fetch_add(5, Ordering::Relaxed);
+        drop(counter);
+        assert_eq!(registry.drain(), vec![(client, 0, 5)]);
+        assert_eq!(registry.len(), 0);
+    }
+}
@@ -0,0 +1,75 @@
+use std::{io, path::Path, pin::Pin};
+use bytes::{Bytes, BytesMut};
+use futures::Stream;
+use tokio::io::AsyncReadExt;
+
+const MIN_CHUNK_SIZE: usize = 64 * 1024;
+const MAX_CHUNK_SIZE: usize = 2 * 1024 * 1024;
+
+pub type FileStream = Pin<Box<dyn Stream<Item = Result<Bytes, io::Error>> + Send>>;
+
+pub fn chunk_size(file_size: usize) -> usize {
+    (file_size / 64).clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE).next_power_of_two()
+}
+
+/// Streams exactly the size announced in Content-Length. A file that gets shorter while it is
+/// being served ends the stream with an error, so the download is reported as aborted.
+pub async fn file_stream(path: &Path) -> io::Result<(FileStream, usize)> {
+    let file = tokio::fs::File::open(path).await?;
+    let size = file.metadata().await?.len() as usize;
+    let chunk = chunk_size(size);
+
+    let stream = futures::stream::try_unfold((file.take(size as u64), size), move |(mut file, remaining)| async move {
+        if remaining == 0 {
+            return Ok(None);
+        }
+        let mut buffer = BytesMut::with_capacity(chunk.min(remaining));
+        match file.read_buf(&mut buffer).await? {
+            0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "file was truncated while being served")),
+            read => Ok(Some((buffer.freeze(), (file, remaining - read)))),
+        }
+    });
+
+    Ok((Box::pin(stream), size))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use super::*;
+
+    fn temp_file(name: &str, size: usize) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("fileshare-stream-{}-{}", name, std::process::id()));
+        std::fs::write(&path, vec![7u8; size]).unwrap();
+        path
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap()
+    }
+
+    #[test]
+    fn whole_files_are_streamed() {
+        let path = temp_file("whole", MIN_CHUNK_SIZE * 3 + 5);
+        runtime().block_on(async {
+            let (stream, size) = file_stream(&path).await.unwrap();
+            let chunks = stream.collect::<Vec<_>>().await;
+            assert_eq!(size, MIN_CHUNK_SIZE * 3 + 5);
+            assert_eq!(chunks.into_iter().map(|chunk| chunk.unwrap().len()).sum::<usize>(), size);
+        });
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn truncating_a_file_while_streaming_ends_with_an_error() {
+        let path = temp_file("truncated", MIN_CHUNK_SIZE * 4);
+        runtime().block_on(async {
+            let (mut stream, _) = file_stream(&path).await.unwrap();
+            assert_eq!(stream.next().await.unwrap().unwrap().len(), MIN_CHUNK_SIZE);
+            std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(MIN_CHUNK_SIZE as u64).unwrap();
+            let error = stream.next().await.unwrap().unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        });
+        let _ = std::fs::remove_file(&path);
+    }
+}
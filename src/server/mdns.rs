@@ -0,0 +1,118 @@
+use std::net::IpAddr;
+use futures::SinkExt;
+use iced::{stream::channel, Subscription};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+const SERVICE_TYPE: &str = "_http._tcp.local.";
+const APP_PROPERTY: &str = "fileshare";
+
+#[derive(Debug, Clone)]
+pub struct DiscoveredInstance {
+    pub fullname: String,
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum MdnsMessage {
+    Found(DiscoveredInstance),
+    Removed { fullname: String },
+}
+
+pub struct Mdns {
+    daemon: Option<ServiceDaemon>,
+    name: String,
+    registered: Option<String>,
+}
+
+impl Mdns {
+    pub fn new() -> Self {
+        let name = gethostname::gethostname()
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect::<String>()
+            .trim_matches('-')
+            .to_string();
+
+        Self {
+            daemon: ServiceDaemon::new().ok(),
+            name: if name.is_empty() { "share".to_string() } else { name },
+            registered: None,
+        }
+    }
+
+    pub fn hostname(&self) -> String {
+        format!("fileshare-{}.local", self.name)
+    }
+
+    pub fn fullname(&self) -> Option<&String> {
+        self.registered.as_ref()
+    }
+
+    pub fn is_advertising(&self) -> bool {
+        self.registered.is_some()
+    }
+
+    pub fn advertise(&mut self, ips: &[IpAddr], port: u16, base_path: &str) {
+        self.stop();
+        let Some(daemon) = &self.daemon else {
+            return;
+        };
+        if ips.is_empty() {
+            return;
+        }
+
+        let instance_name = format!("Fileshare {}", self.name);
+        let path = format!("{}/index", base_path);
+        let properties = [("path", path.as_str()), ("app", APP_PROPERTY)];
+        let service = ServiceInfo::new(SERVICE_TYPE, &instance_name, &format!("{}.", self.hostname()), ips, port, &properties[..]);
+
+        if let Ok(service) = service {
+            let fullname = service.get_fullname().to_string();
+            if daemon.register(service).is_ok() {
+                self.registered = Some(fullname);
+            }
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let (Some(daemon), Some(fullname)) = (&self.daemon, self.registered.take()) {
+            let _ = daemon.unregister(&fullname);
+        }
+    }
+}
+
+pub fn discovery() -> Subscription<MdnsMessage> {
+    Subscription::run(|| channel(100, |mut tx: futures::channel::mpsc::Sender<MdnsMessage>| async move {
+        let Ok(daemon) = ServiceDaemon::new() else {
+            return;
+        };
+        let Ok(receiver) = daemon.browse(SERVICE_TYPE) else {
+            return;
+        };
+
+        while let Ok(event) = receiver.recv_async().await {
+            let message = match event {
+                ServiceEvent::ServiceResolved(info) if info.get_property_val_str("app") == Some(APP_PROPERTY) => {
+                    let Some(ip) = info.get_addresses().iter().find(|ip| ip.is_ipv4()).or(info.get_addresses().iter().next()) else {
+                        continue;
+                    };
+                    let path = info.get_property_val_str("path").unwrap_or("/index");
+                    let host = match ip {
+                        IpAddr::V4(ip) => ip.to_string(),
+                        IpAddr::V6(ip) => format!("[{}]", ip),
+                    };
+                    MdnsMessage::Found(DiscoveredInstance {
+                        fullname: info.get_fullname().to_string(),
+                        name: info.get_fullname().trim_end_matches(SERVICE_TYPE).trim_end_matches('.').to_string(),
+                        url: format!("http://{}:{}{}", host, info.get_port(), path),
+                    })
+                },
+                ServiceEvent::ServiceRemoved(_, fullname) => MdnsMessage::Removed { fullname },
+                _ => continue,
+            };
+            let _ = tx.send(message).await;
+        }
+    }))
+}
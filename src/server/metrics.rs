@@ -0,0 +1,143 @@
+use std::{collections::{BTreeMap, HashMap}, fmt::Write, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex, RwLock}};
+use serde::{Deserialize, Serialize};
+use warp::{http::{header, StatusCode}, reject::Rejection, Filter};
+
+use crate::state::{access_manager::AccessManager, client_manager::ClientManager, file_manager::{FileInfo, FileManager}};
+
+use super::{client_ip::client_ip, connection_tracker::ConnectionTracker, rate_limiter::RateLimiter};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub port: Option<u16>,
+    pub local_only: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: None,
+            local_only: true,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Gauges {
+    clients: AtomicUsize,
+    active_downloads: AtomicUsize,
+    total_downloads: AtomicUsize,
+    transmitted_bytes: AtomicUsize,
+    zip_jobs: AtomicUsize,
+}
+
+#[derive(Clone)]
+pub struct Metrics {
+    config: Arc<RwLock<MetricsConfig>>,
+    gauges: Arc<Gauges>,
+    errors: Arc<Mutex<BTreeMap<u16, usize>>>,
+}
+
+impl Metrics {
+    pub fn new(config: MetricsConfig) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            gauges: Arc::default(),
+            errors: Arc::default(),
+        }
+    }
+
+    pub fn config(&self) -> MetricsConfig {
+        *self.config.read().unwrap()
+    }
+
+    pub fn set_config(&self, config: MetricsConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn update(&self, clients: &ClientManager, files: &FileManager) {
+        self.gauges.clients.store(clients.num_clients(), Ordering::Relaxed);
+        self.gauges.active_downloads.store(clients.active_downloads(), Ordering::Relaxed);
+        self.gauges.total_downloads.store(clients.total_downloads(), Ordering::Relaxed);
+        self.gauges.transmitted_bytes.store(clients.transmitted_data(), Ordering::Relaxed);
+        self.gauges.zip_jobs.store(files.get_zip_compressing().len(), Ordering::Relaxed);
+    }
+
+    pub fn record_status(&self, status: StatusCode) {
+        if status.is_client_error() || status.is_server_error() {
+            *self.errors.lock().unwrap().entry(status.as_u16()).or_default() += 1;
+        }
+    }
+
+    fn render(&self, files: &HashMap<usize, FileInfo>, connections: &ConnectionTracker, limiter: &RateLimiter) -> String {
+        let mut output = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, usize)>| {
+            let _ = writeln!(output, "# HELP fileshare_{} {}", name, help);
+            let _ = writeln!(output, "# TYPE fileshare_{} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(output, "fileshare_{}{} {}", name, labels, value);
+            }
+        };
+        let gauge = |value: &AtomicUsize| vec![(String::new(), value.load(Ordering::Relaxed))];
+
+        metric("active_connections", "gauge", "Open HTTP connections.", vec![(String::new(), connections.total())]);
+        metric("clients", "gauge", "Clients seen since the server started.", gauge(&self.gauges.clients));
+        metric("active_downloads", "gauge", "Clients currently downloading.", gauge(&self.gauges.active_downloads));
+        metric("downloads_total", "counter", "Completed downloads.", gauge(&self.gauges.total_downloads));
+        metric("transmitted_bytes_total", "counter", "Bytes sent to clients.", gauge(&self.gauges.transmitted_bytes));
+        metric("zip_jobs", "gauge", "Folders currently being compressed.", gauge(&self.gauges.zip_jobs));
+
+        let mut files = files.values().collect::<Vec<_>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        metric("file_downloads_total", "counter", "Completed downloads per shared file.", files.into_iter()
+            .map(|file| (format!("{{file=\"{}\"}}", label(&file.path.file_name().unwrap_or_default().to_string_lossy())), file.download_count))
+            .collect());
+
+        metric("request_errors_total", "counter", "Responses with a 4xx or 5xx status.", self.errors.lock().unwrap()
+            .iter()
+            .map(|(status, count)| (format!("{{status=\"{}\"}}", status), *count))
+            .collect());
+
+        let rejected = limiter.rejected();
+        metric("rate_limited_total", "counter", "Requests rejected by rate limits.", vec![
+            ("{limit=\"connections_per_ip\"}".to_string(), rejected.connections_per_ip.load(Ordering::Relaxed)),
+            ("{limit=\"clients\"}".to_string(), rejected.clients.load(Ordering::Relaxed)),
+            ("{limit=\"requests_per_second\"}".to_string(), rejected.requests_per_second.load(Ordering::Relaxed)),
+        ]);
+
+        output
+    }
+}
+
+pub fn metrics_route(
+    metrics: Metrics,
+    files: Arc<RwLock<HashMap<usize, FileInfo>>>,
+    connections: ConnectionTracker,
+    limiter: RateLimiter,
+    access: AccessManager,
+    port: Option<u16>,
+) -> impl Filter<Extract = (warp::reply::Response,), Error = Rejection> + Clone {
+    warp::path!("metrics")
+        .and(warp::get())
+        .and(client_ip(access))
+        .and_then(move |ip: Option<std::net::IpAddr>| {
+            let config = metrics.config();
+            let result = match ip {
+                _ if !config.enabled || config.port != port => Err(warp::reject::not_found()),
+                Some(ip) if config.local_only && !ip.is_loopback() => Err(warp::reject::not_found()),
+                _ => {
+                    let body = metrics.render(&files.read().unwrap(), &connections, &limiter);
+                    Ok(warp::http::Response::builder()
+                        .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+                        .body(body.into())
+                        .unwrap())
+                },
+            };
+            async move { result }
+        })
+}
+
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
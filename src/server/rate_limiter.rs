@@ -0,0 +1,244 @@
+use std::{collections::HashMap, net::IpAddr, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex, RwLock}, time::{Duration, Instant}};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const EVICTION_INTERVAL: Duration = Duration::from_secs(10);
+const CONNECTION_WAIT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    ConnectionsPerIp,
+    Clients,
+    RequestsPerSecond,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Limits {
+    pub connections_per_ip: usize,
+    pub clients: usize,
+    pub requests_per_second: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            connections_per_ip: 5,
+            clients: 0,
+            requests_per_second: 0,
+        }
+    }
+}
+
+impl Limits {
+    pub fn get(&self, kind: LimitKind) -> usize {
+        match kind {
+            LimitKind::ConnectionsPerIp => self.connections_per_ip,
+            LimitKind::Clients => self.clients,
+            LimitKind::RequestsPerSecond => self.requests_per_second,
+        }
+    }
+
+    pub fn set(&mut self, kind: LimitKind, value: usize) {
+        match kind {
+            LimitKind::ConnectionsPerIp => self.connections_per_ip = value,
+            LimitKind::Clients => self.clients = value,
+            LimitKind::RequestsPerSecond => self.requests_per_second = value,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LimitExceeded;
+
+impl warp::reject::Reject for LimitExceeded {}
+
+struct ClientLimit {
+    semaphore: Arc<Semaphore>,
+    permits: usize,
+    window_start: Instant,
+    requests: usize,
+    last_seen: Instant,
+}
+
+#[derive(Default)]
+pub struct RejectedRequests {
+    pub connections_per_ip: AtomicUsize,
+    pub clients: AtomicUsize,
+    pub requests_per_second: AtomicUsize,
+}
+
+impl RejectedRequests {
+    pub fn total(&self) -> usize {
+        self.connections_per_ip.load(Ordering::Relaxed)
+            + self.clients.load(Ordering::Relaxed)
+            + self.requests_per_second.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    limits: Arc<RwLock<Limits>>,
+    clients: Arc<Mutex<HashMap<IpAddr, ClientLimit>>>,
+    last_eviction: Arc<Mutex<Instant>>,
+    rejected: Arc<RejectedRequests>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits: Arc::new(RwLock::new(limits)),
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            last_eviction: Arc::new(Mutex::new(Instant::now())),
+            rejected: Arc::new(RejectedRequests::default()),
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        *self.limits.read().unwrap()
+    }
+
+    pub fn set_limits(&self, limits: Limits) {
+        *self.limits.write().unwrap() = limits;
+        self.clients.lock().unwrap().retain(|_, client| client.semaphore.available_permits() < client.permits);
+    }
+
+    pub fn set_limit(&self, kind: LimitKind, value: usize) {
+        self.limits.write().unwrap().set(kind, value);
+        if kind == LimitKind::ConnectionsPerIp {
+            self.clients.lock().unwrap().retain(|_, client| client.semaphore.available_permits() < client.permits);
+        }
+    }
+
+    pub fn rejected(&self) -> &RejectedRequests {
+        &self.rejected
+    }
+
+    pub fn check(&self, ip: IpAddr) -> Result<(), LimitExceeded> {
+        let limits = self.limits();
+        self.evict_idle();
+
+        let mut clients = self.clients.lock().unwrap();
+        if limits.clients > 0 && !clients.contains_key(&ip) && clients.len() >= limits.clients {
+            self.rejected.clients.fetch_add(1, Ordering::Relaxed);
+            return Err(LimitExceeded);
+        }
+
+        let client = clients.entry(ip).or_insert_with(|| Self::new_client(&limits));
+        client.last_seen = Instant::now();
+        if client.window_start.elapsed() >= Duration::from_secs(1) {
+            client.window_start = Instant::now();
+            client.requests = 0;
+        }
+        client.requests += 1;
+
+        if limits.requests_per_second > 0 && client.requests > limits.requests_per_second {
+            self.rejected.requests_per_second.fetch_add(1, Ordering::Relaxed);
+            return Err(LimitExceeded);
+        }
+        Ok(())
+    }
+
+    /// Waits for a free connection slot so queued downloads start as soon as one finishes.
+    pub async fn acquire_connection(&self, ip: IpAddr) -> Result<OwnedSemaphorePermit, LimitExceeded> {
+        let limits = self.limits();
+        let semaphore = self.clients.lock().unwrap()
+            .entry(ip)
+            .or_insert_with(|| Self::new_client(&limits))
+            .semaphore
+            .clone();
+
+        match tokio::time::timeout(CONNECTION_WAIT, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            _ => {
+                self.rejected.connections_per_ip.fetch_add(1, Ordering::Relaxed);
+                Err(LimitExceeded)
+            },
+        }
+    }
+
+    fn new_client(limits: &Limits) -> ClientLimit {
+        let permits = match limits.connections_per_ip {
+            0 => Semaphore::MAX_PERMITS,
+            n => n,
+        };
+        ClientLimit {
+            semaphore: Arc::new(Semaphore::new(permits)),
+            permits,
+            window_start: Instant::now(),
+            requests: 0,
+            last_seen: Instant::now(),
+        }
+    }
+
+    fn evict_idle(&self) {
+        let mut last_eviction = self.last_eviction.lock().unwrap();
+        if last_eviction.elapsed() < EVICTION_INTERVAL {
+            return;
+        }
+        *last_eviction = Instant::now();
+
+        self.clients.lock().unwrap().retain(|_, client| {
+            client.last_seen.elapsed() < IDLE_TIMEOUT || client.semaphore.available_permits() < client.permits
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use super::*;
+
+    fn ip(last: u8) -> IpAddr {
+        Ipv4Addr::new(192, 168, 1, last).into()
+    }
+
+    #[test]
+    fn requests_per_second_are_counted_per_window() {
+        let limiter = RateLimiter::new(Limits { requests_per_second: 2, ..Limits::default() });
+        assert!(limiter.check(ip(1)).is_ok());
+        assert!(limiter.check(ip(1)).is_ok());
+        assert!(limiter.check(ip(1)).is_err());
+        assert!(limiter.check(ip(2)).is_ok());
+
+        limiter.clients.lock().unwrap().get_mut(&ip(1)).unwrap().window_start -= Duration::from_secs(1);
+        assert!(limiter.check(ip(1)).is_ok());
+        assert_eq!(limiter.rejected().requests_per_second.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn client_limit_only_rejects_new_clients() {
+        let limiter = RateLimiter::new(Limits { clients: 1, ..Limits::default() });
+        assert!(limiter.check(ip(1)).is_ok());
+        assert!(limiter.check(ip(2)).is_err());
+        assert!(limiter.check(ip(1)).is_ok());
+        assert_eq!(limiter.rejected().total(), 1);
+    }
+
+    #[test]
+    fn idle_clients_without_connections_are_evicted() {
+        let limiter = RateLimiter::new(Limits { clients: 2, ..Limits::default() });
+        assert!(limiter.check(ip(1)).is_ok());
+        assert!(limiter.check(ip(2)).is_ok());
+        let permit = limiter.clients.lock().unwrap()[&ip(2)].semaphore.clone().try_acquire_owned().unwrap();
+        for client in limiter.clients.lock().unwrap().values_mut() {
+            client.last_seen -= IDLE_TIMEOUT;
+        }
+        *limiter.last_eviction.lock().unwrap() -= EVICTION_INTERVAL;
+
+        limiter.evict_idle();
+        let clients = limiter.clients.lock().unwrap();
+        assert!(!clients.contains_key(&ip(1)));
+        assert!(clients.contains_key(&ip(2)));
+        drop(permit);
+    }
+
+    #[test]
+    fn eviction_waits_for_its_interval() {
+        let limiter = RateLimiter::new(Limits::default());
+        assert!(limiter.check(ip(1)).is_ok());
+        limiter.clients.lock().unwrap().get_mut(&ip(1)).unwrap().last_seen -= IDLE_TIMEOUT;
+        limiter.evict_idle();
+        assert!(limiter.clients.lock().unwrap().contains_key(&ip(1)));
+    }
+}
@@ -1,49 +1,223 @@
-use std::{collections::HashMap, net::IpAddr, sync::{atomic::{AtomicBool, Ordering}, Arc, RwLock}};
+use std::{collections::HashMap, convert::Infallible, future::Future, net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr}, time::Duration, sync::{atomic::{AtomicBool, Ordering}, Arc, RwLock}};
 use iced::Theme;
-use tokio::sync::Mutex;
-use warp::{reject::Rejection, reply::Reply, Filter};
-use futures::channel::mpsc::Sender;
+use warp::{filters::BoxedFilter, http::StatusCode, hyper::{self, server::conn::AddrStream, service::{make_service_fn, service_fn, Service}, Body, Request, Response, Server}, reject::Rejection, reply::Reply, Filter};
+use tokio_util::sync::CancellationToken;
 
-use crate::{state::file_manager, state::update::ServerMessage};
+use crate::{state::{access_manager::{AccessManager, Approval}, client_manager::ClientId, file_manager, invite_manager::{Invite, InviteManager}}, state::update::ServerMessage};
 
-use super::{download_service::{download_all_route, download_route}, webpage_service::{index_route, refresh_route, static_route}};
+use super::{access_log::{request_info, AccessLog}, client_ip::{client_ip, RemoteAddr}, connection_tracker::ConnectionTracker, event_bus::{EventSender, ProgressRegistry}, download_service::{download_all_route, download_route}, metrics::{metrics_route, Metrics}, rate_limiter::{LimitExceeded, RateLimiter}, session::{session, Session}, webpage_service::{access_page, index_route, refresh_route, static_route}};
+
+#[derive(Debug)]
+struct ApprovalPending;
+
+impl warp::reject::Reject for ApprovalPending {}
+
+#[derive(Debug)]
+struct ApprovalDenied;
+
+impl warp::reject::Reject for ApprovalDenied {}
+
+const FALLBACK_PORTS: u16 = 20;
+const BIND_ATTEMPTS: usize = 5;
+const BIND_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+pub struct ServerState {
+    pub files: Arc<RwLock<HashMap<usize, file_manager::FileInfo>>>,
+    pub invites: InviteManager,
+    pub block_external_connections: Arc<AtomicBool>,
+    pub access: AccessManager,
+    pub limiter: RateLimiter,
+    pub theme: Arc<RwLock<Theme>>,
+    pub base_path: String,
+    pub local_addresses: Vec<IpAddr>,
+    pub connections: ConnectionTracker,
+    pub progress: ProgressRegistry,
+    pub access_log: AccessLog,
+    pub metrics: Metrics,
+}
 
 pub async fn server(
-    ip: IpAddr, 
-    port: u16, 
-    path: Arc<RwLock<HashMap<usize, file_manager::FileInfo>>>, 
-    tx: Sender<ServerMessage>,
-    block_external_connections: Arc<AtomicBool>,
-    theme: Arc<RwLock<Theme>>
+    addresses: Vec<SocketAddr>, 
+    port_fallback: bool,
+    server_state: ServerState,
+    generation: u64,
+    shutdown: CancellationToken,
+    tx: EventSender,
 ) {
-    let routes = warp::any()
-        .and(block_external(block_external_connections))
-        .and(index_route(path.clone(), theme.clone())
-            .or(refresh_route(path.clone(), theme.clone()))
-            .or(static_route())
-            .or(download_route(path, tx.clone(), Arc::new(Mutex::new(HashMap::<IpAddr, Arc<tokio::sync::Semaphore>>::new()))))
-            .or(download_all_route(tx.clone())))
-        .and_then(move |addr: std::net::IpAddr, reply| {
-            notify_application_and_reply(addr, tx.clone(), reply)
+    let ServerState { files: path, invites, block_external_connections, access, limiter, theme, base_path, local_addresses, connections, progress, access_log, metrics } = server_state;
+
+    let static_files = base_path_filter(&base_path)
+        .and(block_external(block_external_connections.clone(), access.clone()))
+        .and(rate_limit(limiter.clone(), access.clone()))
+        .and(static_route())
+        .map(|_, reply| reply);
+
+    let metrics_config = metrics.config();
+    let metrics_routes = base_path_filter(&base_path)
+        .and(block_external(block_external_connections.clone(), access.clone()))
+        .and(metrics_route(metrics.clone(), path.clone(), connections.clone(), limiter.clone(), access.clone(), None))
+        .map(|_, reply| reply);
+    let metrics_service = warp::service(block_external(block_external_connections.clone(), access.clone())
+        .and(metrics_route(metrics.clone(), path.clone(), connections.clone(), limiter.clone(), access.clone(), metrics_config.port))
+        .map(|_, reply| reply));
+
+    let routes = base_path_filter(&base_path)
+        .and(block_external(block_external_connections, access.clone()))
+        .and(rate_limit(limiter.clone(), access.clone()))
+        .and(client_approval(access.clone(), local_addresses, tx.clone()))
+        .and(invite_access(invites.clone()))
+        .and(session())
+        .and(index_route(path.clone(), invites.clone(), theme.clone(), base_path.clone())
+            .or(refresh_route(path.clone(), invites.clone(), theme.clone(), base_path.clone()))
+            .or(download_route(path, invites.clone(), access.clone(), limiter, progress, tx.clone()))
+            .or(download_all_route(invites, access.clone(), tx.clone())))
+        .and_then({
+            let tx = tx.clone();
+            move |ip: std::net::IpAddr, invite: Option<Invite>, session: Session, reply| {
+                let client = ClientId { ip, invite: invite.map(|invite| invite.token), session: session.id.clone() };
+                notify_application_and_reply(client, session, tx.clone(), reply)
+            }
         });
-        
-    warp::serve(routes)
-        .run((ip, port))
-        .await;
+
+    let routes = request_info(access.clone())
+        .and(static_files
+            .or(metrics_routes)
+            .or(routes)
+            .recover(move |rejection| handle_rejection(rejection, theme.clone(), base_path.clone())))
+        .map(move |info, reply| {
+            let response = access_log.log(info, reply);
+            metrics.record_status(response.status());
+            response
+        });
+    let service = warp::service(routes);
+
+    let Some(port) = addresses.first().map(|addr| addr.port()) else {
+        return;
+    };
+    let last_port = match port_fallback {
+        true => port.saturating_add(FALLBACK_PORTS),
+        false => port,
+    };
+
+    let first_port = port;
+    let mut error = String::new();
+    for port in port..=last_port {
+        // Only the requested port is retried, it may still be held by the server that is shutting down.
+        let attempts = if port == first_port { BIND_ATTEMPTS } else { 1 };
+        for attempt in 0..attempts {
+            if shutdown.is_cancelled() {
+                return;
+            }
+
+            let mut bound = Vec::new();
+            let mut servers = Vec::new();
+            for mut addr in addresses.iter().copied() {
+                addr.set_port(port);
+                match bind(addr, service.clone(), connections.clone(), shutdown.clone()) {
+                    Ok((addr, server)) => {
+                        bound.push(addr);
+                        servers.push(server);
+                    },
+                    Err(err) => error = err.to_string(),
+                }
+            }
+
+            if !servers.is_empty() {
+                let _ = tx.unbounded_send(ServerMessage::Started { generation, addresses: bound });
+                let mut metrics_servers = Vec::new();
+                if let (true, Some(metrics_port)) = (metrics_config.enabled, metrics_config.port) {
+                    let metrics_addresses = match metrics_config.local_only {
+                        true => vec![SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), metrics_port), SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), metrics_port)],
+                        false => addresses.iter().map(|addr| SocketAddr::new(addr.ip(), metrics_port)).collect(),
+                    };
+                    let mut error = None;
+                    for addr in metrics_addresses {
+                        match bind(addr, metrics_service.clone(), ConnectionTracker::default(), shutdown.clone()) {
+                            Ok((_, server)) => metrics_servers.push(server),
+                            Err(err) => error = Some(err.to_string()),
+                        }
+                    }
+                    if let (true, Some(error)) = (metrics_servers.is_empty(), error) {
+                        let _ = tx.unbounded_send(ServerMessage::MetricsBindFailed { generation, port: metrics_port, error });
+                    }
+                }
+                futures::future::join(futures::future::join_all(servers), futures::future::join_all(metrics_servers)).await;
+                return;
+            }
+            if attempt + 1 < attempts {
+                tokio::time::sleep(BIND_RETRY_DELAY).await;
+            }
+        }
+    }
+
+    if !shutdown.is_cancelled() {
+        let _ = tx.unbounded_send(ServerMessage::BindFailed { generation, port, error });
+    }
+}
+
+fn bind<S>(
+    addr: SocketAddr,
+    service: S,
+    connections: ConnectionTracker,
+    shutdown: CancellationToken,
+) -> Result<(SocketAddr, impl Future<Output = ()>), hyper::Error>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    let make_service = make_service_fn(move |connection: &AddrStream| {
+        let remote = connection.remote_addr();
+        let guard = connections.open(remote.ip().to_canonical());
+        let service = service.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |mut request: Request<Body>| {
+                let _connection = &guard;
+                let version = request.version();
+                request.extensions_mut().insert(RemoteAddr(remote));
+                request.extensions_mut().insert(version);
+                service.clone().call(request)
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    let addr = server.local_addr();
+    let server = server.with_graceful_shutdown(shutdown.cancelled_owned());
+    Ok((addr, async move {
+        let _ = server.await;
+    }))
+}
+
+pub fn normalize_base_path(base_path: &str) -> String {
+    base_path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", segment))
+        .collect()
+}
+
+fn base_path_filter(base_path: &str) -> BoxedFilter<()> {
+    base_path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .fold(warp::any().boxed(), |filter, segment| {
+            filter.and(warp::path(segment.to_string())).boxed()
+        })
 }
 
 fn block_external(
     block_external: Arc<AtomicBool>,
+    access: AccessManager,
 ) -> impl Filter<Extract = (std::net::IpAddr,), Error = Rejection> + Clone {
-    warp::addr::remote()
-        .and_then(move |addr: Option<std::net::SocketAddr>| {
+    client_ip(access.clone())
+        .and_then(move |ip: Option<IpAddr>| {
             let block_external = block_external.load(Ordering::Relaxed);
+            let access = access.clone();
             async move {
-                let ip = addr
-                    .ok_or_else(|| warp::reject::reject())
-                    .map(|addr| addr.ip())?;
-                    
-                if block_external && !is_private_ip(ip) {
+                let ip = ip.ok_or_else(warp::reject::reject)?;
+
+                if access.is_blocked(ip) {
+                    return Err(warp::reject::custom(ApprovalDenied));
+                }
+                if block_external && !is_private_ip(ip) && !access.is_trusted(ip) {
                     return Err(warp::reject::reject());
                 } 
                 Ok::<_, Rejection>(ip)
@@ -51,20 +225,131 @@ fn block_external(
         })
 }
 
+fn client_approval(
+    access: AccessManager,
+    local_addresses: Vec<IpAddr>,
+    tx: EventSender,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    client_ip(access.clone())
+        .and(session())
+        .and_then(move |ip: Option<IpAddr>, session: Session| {
+            let result = match ip {
+                Some(ip) if access.ask_new_clients() && !access.is_trusted(ip) && !ip.is_loopback() && !local_addresses.contains(&ip) => {
+                    match access.request(ip) {
+                        Approval::Approved => Ok(()),
+                        Approval::Pending => {
+                            let client = ClientId { ip, invite: None, session: session.id };
+                            let _ = tx.unbounded_send(ServerMessage::ClientConnected { client, user_agent: session.user_agent, name: session.name });
+                            Err(warp::reject::custom(ApprovalPending))
+                        },
+                        Approval::Denied => Err(warp::reject::custom(ApprovalDenied)),
+                    }
+                },
+                _ => Ok(()),
+            };
+            async move { result }
+        })
+        .untuple_one()
+}
+
+fn rate_limit(
+    limiter: RateLimiter,
+    access: AccessManager,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    client_ip(access)
+        .and_then(move |ip: Option<IpAddr>| {
+            let result = match ip {
+                Some(ip) => limiter.check(ip).map_err(warp::reject::custom),
+                None => Ok(()),
+            };
+            async move { result }
+        })
+        .untuple_one()
+}
+
+async fn handle_rejection(
+    rejection: Rejection,
+    theme: Arc<RwLock<Theme>>,
+    base_path: String,
+) -> Result<warp::reply::Response, Infallible> {
+    if rejection.find::<LimitExceeded>().is_some() {
+        return Ok(warp::reply::with_status("Too many requests", warp::http::StatusCode::TOO_MANY_REQUESTS).into_response());
+    }
+    let approval = if rejection.find::<ApprovalPending>().is_some() {
+        Approval::Pending
+    } else if rejection.find::<ApprovalDenied>().is_some() {
+        Approval::Denied
+    } else {
+        let status = rejection_status(&rejection);
+        return Ok(warp::reply::with_status(status.canonical_reason().unwrap_or_default(), status).into_response());
+    };
+    let html = access_page(approval, theme, &base_path);
+    Ok(warp::reply::with_status(warp::reply::html(html), warp::http::StatusCode::FORBIDDEN).into_response())
+}
+
+fn rejection_status(rejection: &Rejection) -> StatusCode {
+    if rejection.is_not_found() {
+        StatusCode::NOT_FOUND
+    } else if rejection.find::<warp::reject::MethodNotAllowed>().is_some() {
+        StatusCode::METHOD_NOT_ALLOWED
+    } else if rejection.find::<warp::reject::InvalidQuery>().is_some()
+        || rejection.find::<warp::reject::InvalidHeader>().is_some()
+        || rejection.find::<warp::reject::MissingHeader>().is_some() {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+pub fn invite_access(
+    invites: InviteManager,
+) -> impl Filter<Extract = (Option<Invite>,), Error = Rejection> + Clone {
+    warp::query::<HashMap<String, String>>()
+        .and_then(move |query: HashMap<String, String>| {
+            let invite = query.get("invite").map(|token| {
+                invites.get(token).filter(|invite| !invite.is_expired())
+            });
+            let require_invite = invites.require_invite();
+            async move {
+                match invite {
+                    None if require_invite => Err(warp::reject::not_found()),
+                    None => Ok(None),
+                    Some(Some(invite)) => Ok(Some(invite)),
+                    Some(None) => Err(warp::reject::not_found()),
+                }
+            }
+        })
+}
+
 async fn notify_application_and_reply(
-    ip: std::net::IpAddr,
-    mut tx: Sender<ServerMessage>,
+    client: ClientId,
+    session: Session,
+    tx: EventSender,
     reply: impl Reply,
 ) -> Result<impl Reply, Rejection> {
-    tx.try_send(ServerMessage::ClientConnected { ip })
-        .map_err(|_| warp::reject::reject())?;
+    let reply = session.reply(reply);
+    let _ = tx.unbounded_send(ServerMessage::ClientConnected { client, user_agent: session.user_agent, name: session.name });
     
-    Ok(warp::reply::with_header(reply, "Connection", "close"))
+    Ok(reply)
 }
 
 fn is_private_ip(ip: IpAddr) -> bool {
-    match ip {
-        IpAddr::V4(ipv4) => ipv4.is_loopback() || ipv4.is_private(),
-        IpAddr::V6(ipv6) => ipv6.is_loopback() || ipv6.is_unique_local(),
+    match ip.to_canonical() {
+        IpAddr::V4(ipv4) => ipv4.is_loopback() || ipv4.is_private() || ipv4.is_link_local(),
+        IpAddr::V6(ipv6) => ipv6.is_loopback() || ipv6.is_unique_local() || ipv6.is_unicast_link_local(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_paths_are_normalized() {
+        assert_eq!(normalize_base_path(""), "");
+        assert_eq!(normalize_base_path("/"), "");
+        assert_eq!(normalize_base_path("share"), "/share");
+        assert_eq!(normalize_base_path("/share/"), "/share");
+        assert_eq!(normalize_base_path("//a//b/"), "/a/b");
     }
 }
@@ -0,0 +1,103 @@
+use std::sync::OnceLock;
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::Sha256;
+use warp::{http::header, reject::Rejection, Filter, Reply};
+
+const SESSION_COOKIE: &str = "fileshare_session";
+const NAME_COOKIE: &str = "fileshare_name";
+const COOKIE_MAX_AGE: u64 = 60 * 60 * 24 * 365;
+const NONCE_LENGTH: usize = 24;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: String,
+    pub new: bool,
+    pub user_agent: Option<String>,
+    pub name: Option<String>,
+}
+
+impl Session {
+    pub fn reply(&self, reply: impl Reply) -> warp::reply::Response {
+        let mut response = reply.into_response();
+        if self.new {
+            let cookie = format!("{}={}; Path=/; Max-Age={}; SameSite=Lax; HttpOnly", SESSION_COOKIE, self.id, COOKIE_MAX_AGE);
+            if let Ok(cookie) = cookie.parse() {
+                response.headers_mut().append(header::SET_COOKIE, cookie);
+            }
+        }
+        response
+    }
+}
+
+pub fn session() -> impl Filter<Extract = (Session,), Error = Rejection> + Clone {
+    warp::cookie::optional::<String>(SESSION_COOKIE)
+        .and(warp::cookie::optional::<String>(NAME_COOKIE))
+        .and(warp::header::optional::<String>("user-agent"))
+        .map(|id: Option<String>, name: Option<String>, user_agent: Option<String>| {
+            let name = name
+                .map(|name| percent_encoding::percent_decode_str(&name).decode_utf8_lossy().trim().chars().take(40).collect::<String>())
+                .filter(|name| !name.is_empty());
+            match id.filter(|id| verify(id)) {
+                Some(id) => Session { id, new: false, user_agent, name },
+                // Ids from a previous run or made up by the client are replaced, so one device can't pose as another.
+                None => Session { id: new_id(), new: true, user_agent, name },
+            }
+        })
+}
+
+/// Generated on every start, so ids are only valid for as long as the client list they belong to.
+fn secret() -> &'static [u8; 32] {
+    static SECRET: OnceLock<[u8; 32]> = OnceLock::new();
+    SECRET.get_or_init(rand::random)
+}
+
+fn mac(nonce: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret()).expect("HMAC accepts keys of any length");
+    mac.update(nonce.as_bytes());
+    mac
+}
+
+fn new_id() -> String {
+    let nonce: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(NONCE_LENGTH)
+        .map(char::from)
+        .collect();
+    let signature = mac(&nonce).finalize().into_bytes();
+    let signature: String = signature.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}.{}", nonce, signature)
+}
+
+fn verify(id: &str) -> bool {
+    let Some((nonce, signature)) = id.split_once('.') else {
+        return false;
+    };
+    if nonce.len() != NONCE_LENGTH || signature.len() != 64 || !signature.is_ascii() {
+        return false;
+    }
+    let bytes = (0..signature.len())
+        .step_by(2)
+        .map(|indx| u8::from_str_radix(&signature[indx..indx + 2], 16))
+        .collect::<Result<Vec<_>, _>>();
+    bytes.is_ok_and(|bytes| mac(nonce).verify_slice(&bytes).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_issued_ids_are_accepted() {
+        let id = new_id();
+        assert!(verify(&id));
+        assert_ne!(id, new_id());
+
+        let (nonce, signature) = id.split_once('.').unwrap();
+        let forged = format!("{}.{}", "a".repeat(NONCE_LENGTH), signature);
+        assert!(!verify(&forged));
+        assert!(!verify(nonce));
+        assert!(!verify(&format!("{}.{}", nonce, &signature[..62])));
+        assert!(!verify("abc123"));
+    }
+}
@@ -4,7 +4,9 @@ use serde::Serialize;
 use warp::Filter;
 use tera::Tera;
 
-use crate::{state::file_manager, views::styles::color_multiply};
+use crate::{state::{access_manager::Approval, file_manager, invite_manager::{Invite, InviteManager}}, views::styles::color_multiply};
+
+use super::router::invite_access;
 
 #[derive(Serialize)]
 struct SendColor {
@@ -40,53 +42,63 @@ struct DisplayFileInfo {
     size: String,
 }
 
-pub fn static_route() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+pub fn static_route() -> impl Filter<Extract = (warp::fs::File,), Error = warp::Rejection> + Clone {
     warp::path("static")
         .and(warp::fs::dir("./static"))
 }
 
 pub fn index_route(
     path: Arc<RwLock<HashMap<usize, file_manager::FileInfo>>>, 
-    theme: Arc<RwLock<Theme>>
+    invites: InviteManager,
+    theme: Arc<RwLock<Theme>>,
+    base_path: String,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone 
 {
-    let html_route = warp::path("index")
-        .map(move || {
+    warp::path("index")
+        .and(invite_access(invites))
+        .map(move |invite: Option<Invite>| {
             let path = path.clone();
-            let html_str = fill_template(path, "index.html", theme.clone());
-            let response = warp::reply::html(html_str);
-            response
-        });
-    html_route
+            let html_str = fill_template(path, "index.html", theme.clone(), invite.as_ref(), &base_path);
+            warp::reply::html(html_str)
+        })
 }
 
 pub fn refresh_route(
     path: Arc<RwLock<HashMap<usize, file_manager::FileInfo>>>,
-    theme: Arc<RwLock<Theme>>
+    invites: InviteManager,
+    theme: Arc<RwLock<Theme>>,
+    base_path: String,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone 
 {
-    let refresh_route = warp::path("update-content")
-        .map(move || {
-            let html = fill_template(path.clone(), "file_list.html", theme.clone());
+    warp::path("update-content")
+        .and(invite_access(invites))
+        .map(move |invite: Option<Invite>| {
+            let html = fill_template(path.clone(), "file_list.html", theme.clone(), invite.as_ref(), &base_path);
             let theme = theme.read().unwrap();
             let (primary, secondary, background, dark_background, text, text_secondary, footer) = colors(&theme);
-            let size = size_string(path.read().unwrap().iter().map(|(_, file_manager::FileInfo{size, ..})| size).sum());
-            let response = warp::reply::json(&UpdateData {html, size, primary, secondary, background, dark_background, text, text_secondary, footer  });
-            response
-        });
-    refresh_route
+            let size = size_string(path.read().unwrap()
+                .iter()
+                .filter(|(_, file)| invite.as_ref().is_none_or(|invite| invite.can_access(&file.path)))
+                .map(|(_, file_manager::FileInfo{size, ..})| size)
+                .sum());
+            warp::reply::json(&UpdateData {html, size, primary, secondary, background, dark_background, text, text_secondary, footer  })
+        })
 }
 
 pub fn fill_template(
     path: Arc<RwLock<HashMap<usize, file_manager::FileInfo>>>, 
     template: &'static str,
-    theme: Arc<RwLock<Theme>>
+    theme: Arc<RwLock<Theme>>,
+    invite: Option<&Invite>,
+    base_path: &str,
 ) -> String {
     let tera: Tera = Tera::new("template/*.html").unwrap();
     let mut context = tera::Context::new();
+    context.insert("base", base_path);
 
     let mut path = path.read().unwrap()
         .iter()
+        .filter(|(_, file)| invite.is_none_or(|invite| invite.can_access(&file.path)))
         .map(|(i, f)| (*i, f.clone()))
         .collect::<Vec<_>>();
     path.sort_by_key(|(indx, _)| *indx);
@@ -97,10 +109,27 @@ pub fn fill_template(
         DisplayFileInfo { name, index: *i, size: size_string }
     }).collect();
     context.insert("files", &files);
+    context.insert("query", &invite.map(|invite| format!("?invite={}", invite.token)).unwrap_or_default());
 
     let all_size: usize = path.iter().map(|(_, file_manager::FileInfo{size, ..})| size).sum();
     context.insert("all_size", &size_string(all_size));
 
+    insert_colors(&mut context, &theme);
+
+    tera.render(template, &context).unwrap()
+}
+
+pub fn access_page(approval: Approval, theme: Arc<RwLock<Theme>>, base_path: &str) -> String {
+    let tera: Tera = Tera::new("template/*.html").unwrap();
+    let mut context = tera::Context::new();
+    context.insert("base", base_path);
+    context.insert("pending", &(approval == Approval::Pending));
+    insert_colors(&mut context, &theme);
+
+    tera.render("access.html", &context).unwrap()
+}
+
+fn insert_colors(context: &mut tera::Context, theme: &RwLock<Theme>) {
     let theme = theme.read().unwrap();
     let (primary, secondary, background, dark_background, text, text_secondary, footer) = colors(&theme);
     context.insert("primary", &to_rgb_string(primary));
@@ -110,8 +139,6 @@ pub fn fill_template(
     context.insert("text", &to_rgb_string(text));
     context.insert("text_secondary", &to_rgb_string(text_secondary));
     context.insert("footer", &to_rgb_string(footer));
-
-    tera.render(template, &context).unwrap()
 }
 
 fn to_rgb_string(color: SendColor) -> String {
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, net::IpAddr, sync::{atomic::{AtomicBool, Ordering}, Arc, RwLock}};
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Approval {
+    Pending,
+    Approved,
+    Denied,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessList {
+    Allow,
+    Deny,
+    Proxy,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessRules {
+    pub allow: Vec<IpNet>,
+    pub deny: Vec<IpNet>,
+    #[serde(default)]
+    pub proxies: Vec<IpNet>,
+}
+
+pub fn parse_rule(input: &str) -> Option<IpNet> {
+    let input = input.trim();
+    input.parse::<IpNet>().ok()
+        .or_else(|| input.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+impl AccessRules {
+    fn list_mut(&mut self, list: AccessList) -> &mut Vec<IpNet> {
+        match list {
+            AccessList::Allow => &mut self.allow,
+            AccessList::Deny => &mut self.deny,
+            AccessList::Proxy => &mut self.proxies,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AccessManager {
+    ask_new_clients: Arc<AtomicBool>,
+    /// Keyed by address only: a device without a cookie would otherwise open a new request on every page load.
+    approvals: Arc<RwLock<HashMap<IpAddr, Approval>>>,
+    rules: Arc<RwLock<AccessRules>>,
+    connections: Arc<RwLock<HashMap<IpAddr, CancellationToken>>>,
+}
+
+impl AccessManager {
+    pub fn new(rules: AccessRules, ask_new_clients: bool) -> Self {
+        Self {
+            ask_new_clients: Arc::new(AtomicBool::new(ask_new_clients)),
+            rules: Arc::new(RwLock::new(rules)),
+            ..Self::default()
+        }
+    }
+
+    pub fn ask_new_clients(&self) -> bool {
+        self.ask_new_clients.load(Ordering::Relaxed)
+    }
+
+    pub fn set_ask_new_clients(&self, ask: bool) {
+        self.ask_new_clients.store(ask, Ordering::Relaxed);
+    }
+
+    pub fn request(&self, ip: IpAddr) -> Approval {
+        *self.approvals.write().unwrap()
+            .entry(ip)
+            .or_insert(Approval::Pending)
+    }
+
+    pub fn set(&self, ip: IpAddr, approval: Approval) {
+        self.approvals.write().unwrap().insert(ip, approval);
+    }
+
+    pub fn pending(&self) -> Vec<IpAddr> {
+        let mut pending = self.approvals.read().unwrap()
+            .iter()
+            .filter(|(_, approval)| **approval == Approval::Pending)
+            .map(|(ip, _)| *ip)
+            .collect::<Vec<_>>();
+        pending.sort();
+        pending
+    }
+
+    pub fn rules(&self) -> AccessRules {
+        self.rules.read().unwrap().clone()
+    }
+
+    pub fn set_rules(&self, rules: AccessRules) {
+        *self.rules.write().unwrap() = rules;
+    }
+
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.rules.read().unwrap().allow.iter().any(|net| net.contains(&ip))
+    }
+
+    pub fn is_blocked(&self, ip: IpAddr) -> bool {
+        self.rules.read().unwrap().deny.iter().any(|net| net.contains(&ip))
+    }
+
+    pub fn is_proxy(&self, ip: IpAddr) -> bool {
+        self.rules.read().unwrap().proxies.iter().any(|net| net.contains(&ip))
+    }
+
+    pub fn add_rule(&self, list: AccessList, net: IpNet) {
+        let mut rules = self.rules.write().unwrap();
+        let list = rules.list_mut(list);
+        if !list.contains(&net) {
+            list.push(net);
+        }
+    }
+
+    pub fn remove_rule(&self, list: AccessList, net: IpNet) {
+        self.rules.write().unwrap().list_mut(list).retain(|entry| *entry != net);
+    }
+
+    pub fn connection_token(&self, ip: IpAddr) -> CancellationToken {
+        self.connections.write().unwrap()
+            .entry(ip)
+            .or_default()
+            .clone()
+    }
+
+    pub fn disconnect(&self, ip: IpAddr) {
+        if let Some(token) = self.connections.write().unwrap().remove(&ip) {
+            token.cancel();
+        }
+    }
+
+    pub fn disconnect_all(&self) {
+        for (_, token) in self.connections.write().unwrap().drain() {
+            token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_accept_networks_and_addresses() {
+        assert_eq!(parse_rule("192.168.0.0/16"), "192.168.0.0/16".parse().ok());
+        assert_eq!(parse_rule(" 10.0.0.1 "), "10.0.0.1/32".parse().ok());
+        assert_eq!(parse_rule("fe80::/10"), "fe80::/10".parse().ok());
+        assert_eq!(parse_rule("::1"), "::1/128".parse().ok());
+    }
+
+    #[test]
+    fn disconnecting_all_cancels_every_connection() {
+        let access = AccessManager::default();
+        let first = access.connection_token("10.0.0.1".parse().unwrap());
+        let second = access.connection_token("10.0.0.2".parse().unwrap());
+        access.disconnect_all();
+        assert!(first.is_cancelled() && second.is_cancelled());
+        assert!(!access.connection_token("10.0.0.1".parse().unwrap()).is_cancelled());
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert_eq!(parse_rule(""), None);
+        assert_eq!(parse_rule("localhost"), None);
+        assert_eq!(parse_rule("10.0.0.0/33"), None);
+    }
+}
@@ -0,0 +1,176 @@
+use std::{collections::VecDeque, fs::{File, OpenOptions}, io::{BufRead, BufReader, BufWriter, Write}, net::IpAddr, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+
+const MAX_LOADED_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditEvent {
+    Connected,
+    Downloaded,
+    Aborted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    pub event: AuditEvent,
+    pub ip: IpAddr,
+    pub name: Option<String>,
+    pub file: Option<String>,
+    pub bytes: usize,
+}
+
+impl AuditEntry {
+    pub fn new(event: AuditEvent, ip: IpAddr, name: Option<String>, file: Option<String>, bytes: usize) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            event,
+            ip,
+            name,
+            file,
+            bytes,
+        }
+    }
+
+    pub fn matches(&self, event: Option<AuditEvent>, filter: &str) -> bool {
+        if event.is_some_and(|event| event != self.event) {
+            return false;
+        }
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || self.ip.to_string().contains(&filter)
+            || self.timestamp.contains(&filter)
+            || self.name.as_ref().is_some_and(|name| name.to_lowercase().contains(&filter))
+            || self.file.as_ref().is_some_and(|file| file.to_lowercase().contains(&filter))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Keeps only the newest entries in memory, the file on disk stays complete.
+pub struct AuditLog {
+    path: PathBuf,
+    entries: VecDeque<AuditEntry>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        let mut entries = VecDeque::new();
+        for entry in read_entries(&path) {
+            if entries.len() == MAX_LOADED_ENTRIES {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+
+        Self { path, entries }
+    }
+
+    pub fn entries(&self) -> &VecDeque<AuditEntry> {
+        &self.entries
+    }
+
+    /// Keeps the entry in memory even if writing it to the log file fails.
+    pub fn append(&mut self, entry: AuditEntry) -> std::io::Result<()> {
+        let written = self.write(&entry);
+        if self.entries.len() == MAX_LOADED_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        written
+    }
+
+    fn write(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let line = serde_json::to_string(entry)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Streams every matching entry from the log file, including the ones no longer held in memory.
+    pub fn export(&self, event: Option<AuditEvent>, filter: &str, format: ExportFormat, path: &Path) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let entries = read_entries(&self.path).filter(|entry| entry.matches(event, filter));
+        match format {
+            ExportFormat::Json => {
+                write!(out, "[")?;
+                for (indx, entry) in entries.enumerate() {
+                    write!(out, "{}\n  ", if indx == 0 { "" } else { "," })?;
+                    serde_json::to_writer(&mut out, &entry)?;
+                }
+                writeln!(out, "\n]")?;
+            },
+            ExportFormat::Csv => {
+                writeln!(out, "timestamp,event,ip,name,file,bytes")?;
+                for entry in entries {
+                    writeln!(out, "{},{:?},{},{},{},{}",
+                        entry.timestamp,
+                        entry.event,
+                        entry.ip,
+                        csv_field(entry.name.as_deref()),
+                        csv_field(entry.file.as_deref()),
+                        entry.bytes,
+                    )?;
+                }
+            },
+        }
+        out.flush()
+    }
+}
+
+fn read_entries(path: &Path) -> impl Iterator<Item = AuditEntry> {
+    File::open(path)
+        .map(|file| BufReader::new(file).lines())
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+}
+
+fn csv_field(field: Option<&str>) -> String {
+    match field {
+        // Spreadsheets run cells starting with these as formulas, a leading quote keeps them text.
+        Some(field) if field.starts_with(['=', '+', '-', '@', '\t', '\r']) => format!("\"'{}\"", field.replace('"', "\"\"")),
+        Some(field) => format!("\"{}\"", field.replace('"', "\"\"")),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted() {
+        assert_eq!(csv_field(None), "");
+        assert_eq!(csv_field(Some("report.pdf")), "\"report.pdf\"");
+        assert_eq!(csv_field(Some("say \"hi\".txt")), "\"say \"\"hi\"\".txt\"");
+    }
+
+    #[test]
+    fn csv_formulas_are_neutralized() {
+        assert_eq!(csv_field(Some("=HYPERLINK(\"x\")")), "\"'=HYPERLINK(\"\"x\"\")\"");
+        for prefix in ["+", "-", "@", "\t", "\r"] {
+            assert_eq!(csv_field(Some(&format!("{}1", prefix))), format!("\"'{}1\"", prefix));
+        }
+    }
+
+    #[test]
+    fn failed_writes_are_reported_and_kept_in_memory() {
+        let blocker = std::env::temp_dir().join(format!("fileshare-audit-{}", std::process::id()));
+        std::fs::write(&blocker, b"").unwrap();
+        let mut log = AuditLog::new(blocker.join("history.jsonl"));
+
+        let entry = AuditEntry::new(AuditEvent::Connected, IpAddr::from([127, 0, 0, 1]), None, None, 0);
+        assert!(log.append(entry).is_err());
+        assert_eq!(log.entries().len(), 1);
+
+        std::fs::remove_file(&blocker).unwrap();
+    }
+}
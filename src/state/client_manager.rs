@@ -1,4 +1,26 @@
-use std::{cmp::Reverse, collections::HashMap, net::IpAddr};
+use std::{cmp::Reverse, collections::{HashMap, HashSet, VecDeque}, net::IpAddr};
+
+use crate::server::connection_tracker::ConnectionTracker;
+
+pub const THROUGHPUT_HISTORY: usize = 300;
+const MAX_TRANSFERS: usize = 50;
+const IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ClientId {
+    pub ip: IpAddr,
+    pub invite: Option<String>,
+    pub session: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ClientKey(String);
+
+impl ClientId {
+    pub fn key(&self) -> ClientKey {
+        ClientKey(self.session.clone())
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ClientState {
@@ -7,8 +29,31 @@ pub enum ClientState {
     Disconnected,
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TransferState {
+    Active,
+    Done,
+    Aborted,
+}
+
+pub struct Transfer {
+    pub index: usize,
+    pub name: String,
+    pub size: usize,
+    pub sent: usize,
+    pub speed: usize,
+    pub received_data: usize,
+    pub state: TransferState,
+    pub start: std::time::Instant,
+    pub end: Option<std::time::Instant>,
+}
+
 pub struct ClientInfo {
     pub index: usize,
+    pub ip: IpAddr,
+    pub name: Option<String>,
+    pub device: Option<String>,
+    pub hostname: Option<String>,
     pub download_count: usize,
     pub download_size: usize,
     pub last_connection: std::time::Instant,
@@ -21,12 +66,25 @@ pub struct ClientInfo {
     pub current_downloads_size: usize,
     pub state: ClientState,
     pub current_download_progress: usize,
+    pub history: VecDeque<usize>,
+    pub transfers: VecDeque<Transfer>,
+    pub downloaded_files: HashSet<usize>,
+}
+
+impl ClientInfo {
+    pub fn label(&self) -> Option<&String> {
+        self.name.as_ref()
+            .or(self.hostname.as_ref())
+            .or(self.device.as_ref())
+    }
 }
 
 #[derive(Default)]
 pub struct ClientManager {
-    clients: HashMap<IpAddr, ClientInfo>,
+    clients: HashMap<ClientKey, ClientInfo>,
+    next_index: usize,
     throughput: usize,
+    history: VecDeque<usize>,
     active_connections: usize,
     active_downloads: usize,
     total_downloads: usize,
@@ -58,14 +116,18 @@ impl ClientManager {
         self.transmitted_data
     }
 
-    pub fn sorted_clients(&self) -> Vec<(&IpAddr, &ClientInfo)> {
+    pub fn history(&self) -> &VecDeque<usize> {
+        &self.history
+    }
+
+    pub fn sorted_clients(&self) -> Vec<(&ClientKey, &ClientInfo)> {
         let mut clients: Vec<_> = self.clients.iter().collect();
         clients.sort_by_key(|(_, client)| Reverse(client.index));
         clients
     }
 
-    pub fn add_download(&mut self, ip: IpAddr, file_size: usize) {
-        self.clients.entry(ip).and_modify(|client| {
+    pub fn add_download(&mut self, id: &ClientId, file_size: usize) {
+        self.clients.entry(id.key()).and_modify(|client| {
             client.current_downloads_size += file_size;
             client.last_connection = std::time::Instant::now();
             client.last_download = std::time::Instant::now();
@@ -76,22 +138,54 @@ impl ClientManager {
         }
     }
 
-    pub fn download_done(&mut self, ip: IpAddr) {
-        self.clients.entry(ip).and_modify(|client| {
+    pub fn download_done(&mut self, id: &ClientId, index: usize) -> bool {
+        let mut first_download = false;
+        self.clients.entry(id.key()).and_modify(|client| {
             client.download_count += 1;
             client.last_connection = std::time::Instant::now();
+            first_download = client.downloaded_files.insert(index);
         });
 
         self.total_downloads += 1;
+        first_download
+    }
+
+    pub fn downloaded_all(&self, id: &ClientId, mut files: impl Iterator<Item = usize>) -> bool {
+        self.clients.get(&id.key())
+            .is_some_and(|client| files.all(|index| client.downloaded_files.contains(&index)))
     }
 
-    pub fn add_connection(&mut self, ip: IpAddr) {
-        let len = self.clients.len();
+    pub fn add_connection(&mut self, id: &ClientId, name: Option<String>, user_agent: Option<&str>) -> bool {
+        let reconnected = self.clients.get(&id.key()).is_none_or(|client| client.state == ClientState::Disconnected);
+        // Clients are evicted, so the count can't serve as the index of the next one.
+        if !self.clients.contains_key(&id.key()) {
+            self.next_index += 1;
+        }
+        let device = user_agent.and_then(device_name);
         self.clients
-            .entry(ip)
-            .and_modify(|client| client.last_connection = std::time::Instant::now())
+            .entry(id.key())
+            .and_modify(|client| {
+                client.last_connection = std::time::Instant::now();
+                if client.ip != id.ip {
+                    client.hostname = None;
+                }
+                client.ip = id.ip;
+                if name.is_some() {
+                    client.name = name.clone();
+                }
+                if device.is_some() {
+                    client.device = device.clone();
+                }
+                if client.state == ClientState::Disconnected {
+                    client.state = ClientState::Connected;
+                }
+            })
             .or_insert(ClientInfo { 
-                index: len,
+                index: self.next_index,
+                ip: id.ip,
+                name,
+                device,
+                hostname: None,
                 download_count: 0, 
                 last_connection: std::time::Instant::now(), 
                 download_size: 0, 
@@ -104,11 +198,61 @@ impl ClientManager {
                 current_downloads_size: 0,
                 state: ClientState::Connected,
                 current_download_progress: 0,
+                history: VecDeque::with_capacity(THROUGHPUT_HISTORY),
+                transfers: VecDeque::new(),
+                downloaded_files: HashSet::new(),
             });
+        reconnected
     }
 
-    pub fn download_progress(&mut self, ip: IpAddr, progress: usize) {
-        self.clients.entry(ip).and_modify(|client| {
+    pub fn get(&self, id: &ClientId) -> Option<&ClientInfo> {
+        self.clients.get(&id.key())
+    }
+
+    pub fn set_hostname(&mut self, ip: IpAddr, hostname: String) {
+        for client in self.clients.values_mut().filter(|client| client.ip == ip) {
+            client.hostname = Some(hostname.clone());
+        }
+    }
+
+    pub fn start_transfer(&mut self, id: &ClientId, index: usize, name: String, size: usize) {
+        self.clients.entry(id.key()).and_modify(|client| {
+            if client.transfers.iter().any(|transfer| transfer.index == index && transfer.state == TransferState::Active) {
+                return;
+            }
+            if client.transfers.len() == MAX_TRANSFERS {
+                client.transfers.pop_front();
+            }
+            client.transfers.push_back(Transfer {
+                index,
+                name,
+                size,
+                sent: 0,
+                speed: 0,
+                received_data: 0,
+                state: TransferState::Active,
+                start: std::time::Instant::now(),
+                end: None,
+            });
+        });
+    }
+
+    pub fn finish_transfer(&mut self, id: &ClientId, index: usize, state: TransferState) {
+        self.clients.entry(id.key()).and_modify(|client| {
+            if let Some(transfer) = client.transfers.iter_mut().rev().find(|transfer| transfer.index == index && transfer.state == TransferState::Active) {
+                transfer.state = state;
+                transfer.end = Some(std::time::Instant::now());
+                transfer.speed = 0;
+            }
+        });
+    }
+
+    pub fn download_progress(&mut self, id: &ClientId, index: usize, progress: usize) {
+        self.clients.entry(id.key()).and_modify(|client| {
+            if let Some(transfer) = client.transfers.iter_mut().rev().find(|transfer| transfer.index == index && transfer.state == TransferState::Active) {
+                transfer.sent += progress;
+                transfer.received_data += progress;
+            }
             if client.state != ClientState::Downloading {
                 client.current_downloads_size = client.canceled_download_size;
                 client.canceled_download_size = 0;
@@ -130,21 +274,24 @@ impl ClientManager {
         self.transmitted_data += progress;
     }
 
-    pub fn update(&mut self) {
-        let mut active = 0;
+    pub fn update(&mut self, connections: &ConnectionTracker) {
         let mut downloading = 0;
 
         for (_, client) in self.clients.iter_mut() {
             client.speed = client.received_data;
             client.received_data = 0;
             client.max_speed = client.speed.max(client.max_speed);
+            push_sample(&mut client.history, client.speed);
+            for transfer in client.transfers.iter_mut().filter(|transfer| transfer.state == TransferState::Active) {
+                transfer.speed = transfer.received_data;
+                transfer.received_data = 0;
+            }
 
             if client.last_download.elapsed().as_millis() < 2000 {
                 client.state = ClientState::Downloading;
                 downloading += 1;
-            } else if client.last_connection.elapsed().as_millis() < 4000 {
+            } else if connections.count(client.ip) > 0 || client.last_connection.elapsed().as_millis() < 4000 {
                 client.state = ClientState::Connected;
-                active += 1;
             } else {
                 client.state = ClientState::Disconnected;
             }
@@ -156,8 +303,28 @@ impl ClientManager {
                 client.current_download_progress = 0;
             }
         }
-        self.throughput = self.clients.iter().map(|(_, client)| client.speed).sum();
-        self.active_connections = active + downloading;
+        // Clients that stayed away for long are forgotten, so devices that never keep their cookie don't pile up.
+        self.clients.retain(|_, client| client.state != ClientState::Disconnected || client.last_connection.elapsed() < IDLE_TIMEOUT);
+        self.throughput = self.clients.values().map(|client| client.speed).sum();
+        push_sample(&mut self.history, self.throughput);
+        self.active_connections = connections.total();
         self.active_downloads = downloading;
     }
-}
\ No newline at end of file
+}
+
+fn push_sample(history: &mut VecDeque<usize>, sample: usize) {
+    if history.len() == THROUGHPUT_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(sample);
+}
+
+fn device_name(user_agent: &str) -> Option<String> {
+    let result = woothee::parser::Parser::new().parse(user_agent)?;
+    match (result.name, result.os) {
+        (woothee::woothee::VALUE_UNKNOWN, woothee::woothee::VALUE_UNKNOWN) => None,
+        (name, woothee::woothee::VALUE_UNKNOWN) => Some(name.to_string()),
+        (woothee::woothee::VALUE_UNKNOWN, os) => Some(os.to_string()),
+        (name, os) => Some(format!("{} on {}", name, os)),
+    }
+}
@@ -0,0 +1,216 @@
+use std::{fs::OpenOptions, io::Write, path::PathBuf, sync::{Arc, Mutex}};
+use futures::channel::oneshot;
+use qrcode_generator::QrCodeEcc;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{paths::paths, single_instance::send};
+
+const TOKEN_LENGTH: usize = 32;
+const USAGE: &str = "usage: fileshare ctl <add PATH... | list | remove INDEX | clear | start | stop | port PORT | public | local | url [--qr] | status>";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlCommand {
+    Open { paths: Vec<PathBuf> },
+    Add { paths: Vec<PathBuf> },
+    List,
+    Remove { index: usize },
+    Clear,
+    Start,
+    Stop,
+    Port { port: u16 },
+    Address { public: bool },
+    Url { qr: bool },
+    Status,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub token: String,
+    #[serde(flatten)]
+    pub command: ControlCommand,
+}
+
+/// Carries the reply for a control request through `Message`, which has to be `Clone`.
+#[derive(Debug, Clone)]
+pub struct Responder(Arc<Mutex<Option<oneshot::Sender<Value>>>>);
+
+impl Responder {
+    pub fn new() -> (Self, oneshot::Receiver<Value>) {
+        let (tx, rx) = oneshot::channel();
+        (Self(Arc::new(Mutex::new(Some(tx)))), rx)
+    }
+
+    pub fn respond(&self, response: Value) {
+        if let Some(tx) = self.0.lock().unwrap().take() {
+            let _ = tx.send(response);
+        }
+    }
+}
+
+pub fn ok(fields: Value) -> Value {
+    let mut response = json!({ "ok": true });
+    if let (Some(response), Value::Object(fields)) = (response.as_object_mut(), fields) {
+        response.extend(fields);
+    }
+    response
+}
+
+pub fn error(message: impl ToString) -> Value {
+    json!({ "ok": false, "error": message.to_string() })
+}
+
+fn token_path() -> PathBuf {
+    paths().config.join("control.token")
+}
+
+pub fn read_token() -> Option<String> {
+    std::fs::read_to_string(token_path()).ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+pub fn load_or_create_token() -> String {
+    if let Some(token) = read_token() {
+        return token;
+    }
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+    let _ = std::fs::create_dir_all(&paths().config);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    // The file must never be readable by others, not even for a moment before a chmod.
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    match options.open(token_path()) {
+        Ok(mut file) => {
+            let _ = file.write_all(token.as_bytes());
+            token
+        },
+        // Another instance created it first.
+        Err(_) => read_token().unwrap_or(token),
+    }
+}
+
+/// The release build has no console of its own, so borrow the one `fileshare ctl` was started from.
+#[cfg(target_os = "windows")]
+pub fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+pub fn qr_text(url: &str) -> Option<String> {
+    const QUIET_ZONE: usize = 2;
+    let matrix = qrcode_generator::to_matrix(url, QrCodeEcc::Low).ok()?;
+    let size = matrix.len() + QUIET_ZONE * 2;
+    // Light modules are drawn as blocks so the code scans on dark terminals, like `qrencode -t UTF8`.
+    let light = |row: usize, col: usize| {
+        row < QUIET_ZONE || col < QUIET_ZONE || row >= size - QUIET_ZONE || col >= size - QUIET_ZONE
+            || !matrix[row - QUIET_ZONE][col - QUIET_ZONE]
+    };
+    let lines = (0..size).step_by(2)
+        .map(|row| (0..size)
+            .map(|col| match (light(row, col), row + 1 < size && light(row + 1, col)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            })
+            .collect::<String>())
+        .collect::<Vec<_>>();
+    Some(lines.join("\n"))
+}
+
+fn parse_args(args: &[String]) -> Result<ControlCommand, String> {
+    let absolute = |paths: &[String]| paths.iter()
+        .map(|path| std::path::absolute(path).map_err(|err| format!("{}: {}", path, err)))
+        .collect::<Result<Vec<_>, _>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["add", paths @ ..] if !paths.is_empty() => Ok(ControlCommand::Add { paths: absolute(&args[1..])? }),
+        ["list"] => Ok(ControlCommand::List),
+        ["remove", index] => index.parse().map(|index| ControlCommand::Remove { index }).map_err(|_| format!("invalid index: {}", index)),
+        ["clear"] => Ok(ControlCommand::Clear),
+        ["start"] => Ok(ControlCommand::Start),
+        ["stop"] => Ok(ControlCommand::Stop),
+        ["port", port] => port.parse().map(|port| ControlCommand::Port { port }).map_err(|_| format!("invalid port: {}", port)),
+        ["public"] => Ok(ControlCommand::Address { public: true }),
+        ["local"] => Ok(ControlCommand::Address { public: false }),
+        ["url"] => Ok(ControlCommand::Url { qr: false }),
+        ["url", "--qr"] => Ok(ControlCommand::Url { qr: true }),
+        ["status"] => Ok(ControlCommand::Status),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Runs `fileshare ctl ...` against the running instance and returns the process exit code.
+pub fn run_cli(args: &[String]) -> i32 {
+    let command = match parse_args(args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 2;
+        },
+    };
+    let Some(token) = read_token() else {
+        println!("{}", error("Fileshare is not running"));
+        return 1;
+    };
+    let response = send(&Request { token, command }).unwrap_or_else(error);
+    if let Some(qr) = response.get("qr").and_then(Value::as_str) {
+        eprintln!("{}", qr);
+    }
+    println!("{}", serde_json::to_string_pretty(&response).unwrap_or_default());
+    match response.get("ok").and_then(Value::as_bool) {
+        Some(true) => 0,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<ControlCommand, String> {
+        parse_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        assert!(matches!(parse(&["list"]), Ok(ControlCommand::List)));
+        assert!(matches!(parse(&["remove", "3"]), Ok(ControlCommand::Remove { index: 3 })));
+        assert!(matches!(parse(&["port", "8080"]), Ok(ControlCommand::Port { port: 8080 })));
+        assert!(matches!(parse(&["public"]), Ok(ControlCommand::Address { public: true })));
+        assert!(matches!(parse(&["local"]), Ok(ControlCommand::Address { public: false })));
+        assert!(matches!(parse(&["url", "--qr"]), Ok(ControlCommand::Url { qr: true })));
+    }
+
+    #[test]
+    fn added_paths_are_absolute() {
+        let Ok(ControlCommand::Add { paths }) = parse(&["add", "a.txt", "/tmp/b.txt"]) else {
+            panic!("expected add");
+        };
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|path| path.is_absolute()));
+        assert!(paths[0].ends_with("a.txt"));
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported() {
+        assert_eq!(parse(&[]).unwrap_err(), USAGE);
+        assert_eq!(parse(&["add"]).unwrap_err(), USAGE);
+        assert_eq!(parse(&["url", "--png"]).unwrap_err(), USAGE);
+        assert_eq!(parse(&["port", "70000"]).unwrap_err(), "invalid port: 70000");
+        assert_eq!(parse(&["remove", "x"]).unwrap_err(), "invalid index: x");
+    }
+}
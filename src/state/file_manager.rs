@@ -6,6 +6,9 @@ use ignore::WalkBuilder;
 use tokio::task::{self, yield_now};
 use zip::write::SimpleFileOptions;
 use iced::task::Handle;
+use serde::{Deserialize, Serialize};
+
+use super::paths::paths;
 
 #[derive(Debug, Clone)]
 pub enum ZipMessage {
@@ -20,6 +23,7 @@ pub struct CompressingZip {
     pub progress: usize,
     pub handle: Handle,
     pub start_time: std::time::Instant,
+    pub entry: SharedEntry,
 }
 
 #[derive(Debug, Clone)]
@@ -30,11 +34,35 @@ pub struct FileInfo {
     pub is_zip: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SharedEntry {
+    File(PathBuf),
+    Zip { folder: PathBuf, use_gitignore: bool, ignore_hidden: bool },
+}
+
+impl SharedEntry {
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            SharedEntry::File(path) => path,
+            SharedEntry::Zip { folder, .. } => folder,
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        match self {
+            SharedEntry::File(path) => path.is_file(),
+            SharedEntry::Zip { folder, .. } => folder.is_dir(),
+        }
+    }
+}
+
 pub struct FileManager {
     paths: Arc<RwLock<HashMap<usize, FileInfo>>>,
     view: Vec<(usize, FileInfo)>,
     index: usize,
     compressing_zips: HashMap<PathBuf, CompressingZip>,
+    zip_sources: HashMap<PathBuf, SharedEntry>,
+    missing: Vec<SharedEntry>,
 }
 
 impl FileManager {
@@ -44,14 +72,13 @@ impl FileManager {
             view: Vec::new(),
             index: 0,
             compressing_zips: HashMap::new(),
+            zip_sources: HashMap::new(),
+            missing: Vec::new(),
         }
     }
 
     pub fn temp_dir() -> PathBuf{
-        #[cfg(not(feature = "appdata"))]
-        return PathBuf::from(".\\temp");
-        #[cfg(feature = "appdata")]
-        return PathBuf::from(format!("{}\\Fileshare\\temp", std::env::var("APPDATA").unwrap_or_else(|_| String::from("./appdata/temp"))));
+        paths().temp.clone()
     }
 
     
@@ -60,12 +87,13 @@ impl FileManager {
         Path::new(&FileManager::temp_dir()).join(file_name).with_extension("zip")
     }
 
-    pub fn add_new_zip_compressing(&mut self, path: PathBuf, handle: Handle) {
-        self.compressing_zips.insert(path, CompressingZip {
+    pub fn add_new_zip_compressing(&mut self, path: PathBuf, handle: Handle, use_gitignore: bool, ignore_hidden: bool) {
+        self.compressing_zips.insert(path.clone(), CompressingZip {
             num_files: 0,
             progress: 0,
             handle,
             start_time: std::time::Instant::now(),
+            entry: SharedEntry::Zip { folder: path, use_gitignore, ignore_hidden },
         });
     }
 
@@ -91,26 +119,27 @@ impl FileManager {
 
     pub fn already_compressed(&self, path: &PathBuf) -> bool {
         self.compressing_zips.contains_key(path) ||
-        self.view.iter().any(|(_, file)| file.path.file_name().unwrap() == PathBuf::from(Self::temp_dir()).join(path.file_name().unwrap()).with_extension("zip").file_name().unwrap())
+        self.view.iter().any(|(_, file)| file.path.file_name().unwrap() == Self::temp_dir().join(path.file_name().unwrap()).with_extension("zip").file_name().unwrap())
     }
 
-    pub fn zip_compressing_done(&mut self, path: &PathBuf) {
-        self.compressing_zips.remove(path);
-
+    pub fn zip_compressing_done(&mut self, path: &PathBuf) -> PathBuf {
         let write_dir = Self::path_to_zip(path);
+        if let Some(zip) = self.compressing_zips.remove(path) {
+            self.zip_sources.insert(write_dir.clone(), zip.entry);
+        }
 
         if !self.view.iter().any(|(_, file)| file.path == write_dir) {
-            self.push(write_dir, true);
+            self.push(write_dir.clone(), true);
         }
+        write_dir
     }
 
     pub fn get_zip_compressing(&self) -> Vec<(&PathBuf, &CompressingZip)> {
         let mut result = self.compressing_zips
             .iter()
-            .map(|(p, z)| (p, z))
             .collect::<Vec<_>>();
 
-        result.sort_by(|(_, a), (_, b)| a.start_time.cmp(&b.start_time));
+        result.sort_by_key(|(_, zip)| zip.start_time);
     
         result
     }
@@ -138,7 +167,10 @@ impl FileManager {
         if self.view.iter().any(|(_, file)| file.path == path) {
             return;
         }
-        let size = path.metadata().unwrap().len() as usize;
+        let Ok(metadata) = path.metadata() else {
+            return;
+        };
+        let size = metadata.len() as usize;
         let file = FileInfo {
             path,
             size,
@@ -151,10 +183,9 @@ impl FileManager {
     }
 
     pub fn remove(&mut self, index: usize) {
-        if let Some(file) = self.paths.write().unwrap().remove(&index) {
-            if file.is_zip {
-                let _ = std::fs::remove_file(&file.path);
-            }
+        if let Some(file) = self.paths.write().unwrap().remove(&index) && file.is_zip {
+            let _ = std::fs::remove_file(&file.path);
+            self.zip_sources.remove(&file.path);
         }
 
         self.view.retain(|(i, _)| *i != index);
@@ -169,6 +200,38 @@ impl FileManager {
 
         self.paths.write().unwrap().clear();
         self.view.clear();
+        self.zip_sources.clear();
+        self.missing.clear();
+    }
+
+    pub fn shared_entries(&self) -> Vec<SharedEntry> {
+        let mut entries = self.missing.clone();
+        entries.extend(self.view.iter().filter_map(|(_, file)| match file.is_zip {
+            true => self.zip_sources.get(&file.path).cloned(),
+            false => Some(SharedEntry::File(file.path.clone())),
+        }));
+        entries.extend(self.get_zip_compressing().into_iter().map(|(_, zip)| zip.entry.clone()));
+        entries
+    }
+
+    pub fn add_missing(&mut self, entry: SharedEntry) {
+        if !self.missing.contains(&entry) {
+            self.missing.push(entry);
+        }
+    }
+
+    pub fn get_missing(&self) -> &Vec<SharedEntry> {
+        &self.missing
+    }
+
+    pub fn remove_missing(&mut self, index: usize) {
+        if index < self.missing.len() {
+            self.missing.remove(index);
+        }
+    }
+
+    pub fn take_missing(&mut self) -> Vec<SharedEntry> {
+        std::mem::take(&mut self.missing)
     }
 
     pub async fn zip_task(path: PathBuf, mut tx: futures::channel::mpsc::Sender<ZipMessage>, use_gitignore: bool, ignore_hidden: bool) {
@@ -0,0 +1,184 @@
+use std::{net::IpAddr, path::PathBuf, time::Duration};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HookEvent {
+    Downloaded,
+    ClientConnected,
+    ZipDone,
+}
+
+impl HookEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            HookEvent::Downloaded => "downloaded",
+            HookEvent::ClientConnected => "client_connected",
+            HookEvent::ZipDone => "zip_done",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HookKind {
+    Command,
+    Webhook,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hook {
+    pub event: HookEvent,
+    pub kind: HookKind,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    pub file: Option<PathBuf>,
+    pub ip: Option<IpAddr>,
+    pub client: Option<String>,
+    pub bytes: Option<usize>,
+}
+
+impl HookContext {
+    fn payload(&self, event: HookEvent) -> Value {
+        json!({
+            "event": event.name(),
+            "file": self.file.as_ref().map(|file| file.to_string_lossy()),
+            "file_name": self.file.as_ref().and_then(|file| file.file_name()).map(|name| name.to_string_lossy()),
+            "client_ip": self.ip,
+            "client_name": self.client,
+            "bytes": self.bytes,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HookResult {
+    pub timestamp: String,
+    pub hook: Hook,
+    pub outcome: Result<String, String>,
+}
+
+pub async fn run_hook(hook: Hook, context: HookContext) -> HookResult {
+    let payload = context.payload(hook.event);
+    let execution = async {
+        match hook.kind {
+            HookKind::Command => run_command(&hook.target, &payload).await,
+            HookKind::Webhook => post_webhook(&hook.target, &payload).await,
+        }
+    };
+    let outcome = match tokio::time::timeout(HOOK_TIMEOUT, execution).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(format!("Timed out after {}s", HOOK_TIMEOUT.as_secs())),
+    };
+    HookResult {
+        timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
+        hook,
+        outcome,
+    }
+}
+
+async fn run_command(command: &str, payload: &Value) -> Result<String, String> {
+    #[cfg(target_os = "windows")]
+    let mut process = {
+        let mut process = tokio::process::Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut process = {
+        let mut process = tokio::process::Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    };
+
+    let variables = payload.as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| (
+            format!("FILESHARE_{}", key.to_uppercase()),
+            value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()),
+        ));
+
+    let output = process
+        .envs(variables)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    match output.status.success() {
+        true => Ok(format!("{} {}", output.status, stdout).trim().to_string()),
+        false => Err(format!("{} {}", output.status, stderr).trim().to_string()),
+    }
+}
+
+async fn post_webhook(url: &str, payload: &Value) -> Result<String, String> {
+    let uri = url.parse::<hyper::Uri>().map_err(|err| err.to_string())?;
+    if uri.scheme_str() != Some("http") {
+        return Err("Only http:// URLs are supported".to_string());
+    }
+    let host = uri.host().ok_or("Missing host")?;
+    let port = uri.port_u16().unwrap_or(80);
+
+    // Connect to the checked address itself so a second DNS answer can't point the request elsewhere.
+    let addr = tokio::net::lookup_host((host.trim_start_matches('[').trim_end_matches(']'), port))
+        .await
+        .map_err(|err| err.to_string())?
+        .next()
+        .ok_or("Host not found")?;
+    if !is_local_target(addr.ip()) {
+        return Err(format!("{} is not a loopback or private address", addr.ip()));
+    }
+    let path = uri.path_and_query().map(|path| path.as_str()).unwrap_or("/");
+    let target = format!("http://{}{}", addr, path).parse::<hyper::Uri>().map_err(|err| err.to_string())?;
+    let host_header = uri.authority().map(|authority| authority.as_str()).unwrap_or(host);
+
+    let request = hyper::Request::post(target)
+        .header(hyper::header::HOST, host_header)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(hyper::Body::from(payload.to_string()))
+        .map_err(|err| err.to_string())?;
+
+    let response = hyper::Client::new()
+        .request(request)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    match response.status().is_success() {
+        true => Ok(response.status().to_string()),
+        false => Err(response.status().to_string()),
+    }
+}
+
+fn is_local_target(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        IpAddr::V6(ip) => ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_targets_are_allowed() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.10", "169.254.1.1", "::1", "fd00::1", "fe80::1", "::ffff:192.168.1.10"] {
+            assert!(is_local_target(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn public_targets_are_rejected() {
+        for ip in ["8.8.8.8", "172.32.0.1", "0.0.0.0", "2001:db8::1", "::ffff:8.8.8.8"] {
+            assert!(!is_local_target(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc, RwLock}, time::{Duration, SystemTime}};
+use enum_all_variants::AllVariants;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+
+const TOKEN_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invite {
+    pub name: String,
+    pub token: String,
+    // Files are kept by path, indices change when the share is restored after a restart.
+    pub files: Option<HashSet<PathBuf>>,
+    pub expires: Option<SystemTime>,
+    pub created: SystemTime,
+    #[serde(default)]
+    pub downloads: usize,
+    #[serde(default)]
+    pub downloaded_bytes: usize,
+}
+
+impl Invite {
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires <= SystemTime::now())
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        self.expires.map(|expires| expires.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+
+    pub fn can_access(&self, path: &Path) -> bool {
+        !self.is_expired() && self.files.as_ref().is_none_or(|files| files.contains(path))
+    }
+}
+
+#[derive(Debug, AllVariants, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InviteExpiry {
+    #[default]
+    Never,
+    OneHour,
+    OneDay,
+    OneWeek,
+}
+
+impl InviteExpiry {
+    fn duration(&self) -> Option<Duration> {
+        match self {
+            InviteExpiry::Never => None,
+            InviteExpiry::OneHour => Some(Duration::from_secs(60 * 60)),
+            InviteExpiry::OneDay => Some(Duration::from_secs(24 * 60 * 60)),
+            InviteExpiry::OneWeek => Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        }
+    }
+}
+
+impl std::fmt::Display for InviteExpiry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InviteExpiry::Never => write!(f, "∞"),
+            InviteExpiry::OneHour => write!(f, "1h"),
+            InviteExpiry::OneDay => write!(f, "24h"),
+            InviteExpiry::OneWeek => write!(f, "7d"),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct InviteManager {
+    invites: Arc<RwLock<HashMap<String, Invite>>>,
+    require_invite: Arc<AtomicBool>,
+}
+
+impl InviteManager {
+    pub fn new(invites: Vec<Invite>, require_invite: bool) -> Self {
+        Self {
+            invites: Arc::new(RwLock::new(invites.into_iter().map(|invite| (invite.token.clone(), invite)).collect())),
+            require_invite: Arc::new(AtomicBool::new(require_invite)),
+        }
+    }
+
+    pub fn require_invite(&self) -> bool {
+        self.require_invite.load(Ordering::Relaxed)
+    }
+
+    pub fn set_require_invite(&self, require: bool) {
+        self.require_invite.store(require, Ordering::Relaxed);
+    }
+
+    pub fn get(&self, token: &str) -> Option<Invite> {
+        self.invites.read().unwrap().get(token).cloned()
+    }
+
+    pub fn get_view(&self) -> Vec<Invite> {
+        let mut invites = self.invites.read().unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        invites.sort_by_key(|invite| invite.created);
+        invites
+    }
+
+    pub fn create(&mut self, name: String, expiry: InviteExpiry) -> String {
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+        let created = SystemTime::now();
+        let invite = Invite {
+            name,
+            token: token.clone(),
+            files: None,
+            expires: expiry.duration().map(|duration| created + duration),
+            created,
+            downloads: 0,
+            downloaded_bytes: 0,
+        };
+        self.invites.write().unwrap().insert(token.clone(), invite);
+        token
+    }
+
+    pub fn remove(&mut self, token: &str) {
+        self.invites.write().unwrap().remove(token);
+    }
+
+    pub fn toggle_file(&mut self, token: &str, path: PathBuf, all_files: impl Iterator<Item = PathBuf>) {
+        if let Some(invite) = self.invites.write().unwrap().get_mut(token) {
+            let files = invite.files.get_or_insert_with(|| all_files.collect());
+            if !files.remove(&path) {
+                files.insert(path);
+            }
+        }
+    }
+
+    pub fn add_download(&mut self, token: &str, bytes: usize) {
+        if let Some(invite) = self.invites.write().unwrap().get_mut(token) {
+            invite.downloads += 1;
+            invite.downloaded_bytes += bytes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invite(files: Option<&[&str]>, expires: Option<SystemTime>) -> Invite {
+        Invite {
+            name: "Alice".to_string(),
+            token: "token".to_string(),
+            files: files.map(|files| files.iter().map(PathBuf::from).collect()),
+            expires,
+            created: SystemTime::now(),
+            downloads: 0,
+            downloaded_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn without_selection_every_file_is_accessible() {
+        assert!(invite(None, None).can_access(Path::new("/a.txt")));
+    }
+
+    #[test]
+    fn selection_limits_the_accessible_files() {
+        let invite = invite(Some(&["/a.txt"]), None);
+        assert!(invite.can_access(Path::new("/a.txt")));
+        assert!(!invite.can_access(Path::new("/b.txt")));
+    }
+
+    #[test]
+    fn expired_invites_have_no_access() {
+        let expired = invite(None, Some(SystemTime::now() - Duration::from_secs(1)));
+        assert!(expired.is_expired());
+        assert!(!expired.can_access(Path::new("/a.txt")));
+
+        let valid = invite(None, Some(SystemTime::now() + Duration::from_secs(60)));
+        assert!(valid.can_access(Path::new("/a.txt")));
+    }
+
+    #[test]
+    fn toggling_starts_from_all_files() {
+        let mut manager = InviteManager::new(vec![invite(None, None)], false);
+        let all_files = ["/a.txt", "/b.txt"].into_iter().map(PathBuf::from);
+        manager.toggle_file("token", PathBuf::from("/a.txt"), all_files);
+        let invite = manager.get("token").unwrap();
+        assert!(!invite.can_access(Path::new("/a.txt")));
+        assert!(invite.can_access(Path::new("/b.txt")));
+    }
+
+    #[test]
+    fn invites_survive_a_round_trip() {
+        let json = serde_json::to_string(&vec![invite(Some(&["/a.txt"]), None)]).unwrap();
+        let manager = InviteManager::new(serde_json::from_str(&json).unwrap(), true);
+        assert!(manager.require_invite());
+        assert!(manager.get("token").unwrap().can_access(Path::new("/a.txt")));
+    }
+}
@@ -0,0 +1,144 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use local_ip_address::local_ip;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub ip: IpAddr,
+    pub scope_id: u32,
+}
+
+impl NetworkInterface {
+    fn socket_addr(&self, port: u16) -> SocketAddr {
+        match self.ip {
+            IpAddr::V6(ip) if ip.is_unicast_link_local() => SocketAddr::V6(SocketAddrV6::new(ip, port, 0, self.scope_id)),
+            ip => SocketAddr::new(ip, port),
+        }
+    }
+}
+
+impl std::fmt::Display for NetworkInterface {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.ip)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InterfaceSelection {
+    #[default]
+    Automatic,
+    All,
+    Selected(Vec<IpAddr>),
+}
+
+pub struct NetworkManager {
+    interfaces: Vec<NetworkInterface>,
+    automatic: Option<IpAddr>,
+    selection: InterfaceSelection,
+}
+
+impl NetworkManager {
+    pub fn new(selection: InterfaceSelection) -> Self {
+        let mut network_manager = Self {
+            interfaces: Vec::new(),
+            automatic: None,
+            selection,
+        };
+        network_manager.refresh();
+        network_manager
+    }
+
+    pub fn refresh(&mut self) {
+        let mut interfaces = if_addrs::get_if_addrs()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|interface| !interface.is_loopback() && !interface.ip().is_unspecified())
+            .map(|interface| NetworkInterface {
+                ip: interface.ip(),
+                scope_id: interface.index.unwrap_or(0),
+                name: interface.name,
+            })
+            .collect::<Vec<_>>();
+        interfaces.sort_by_key(|interface| (interface.ip.is_ipv6(), interface.name.clone(), interface.ip));
+        interfaces.dedup();
+
+        self.interfaces = interfaces;
+        self.automatic = local_ip().ok();
+    }
+
+    pub fn interfaces(&self) -> &[NetworkInterface] {
+        &self.interfaces
+    }
+
+    pub fn selection(&self) -> &InterfaceSelection {
+        &self.selection
+    }
+
+    pub fn set_selection(&mut self, selection: InterfaceSelection) {
+        self.selection = selection;
+    }
+
+    pub fn toggle(&mut self, ip: IpAddr) {
+        let mut selected = self.display_addresses()
+            .into_iter()
+            .map(|interface| interface.ip)
+            .collect::<Vec<_>>();
+
+        match selected.iter().position(|selected| *selected == ip) {
+            Some(position) => { selected.remove(position); },
+            None => selected.push(ip),
+        }
+        self.selection = InterfaceSelection::Selected(selected);
+    }
+
+    pub fn is_selected(&self, ip: IpAddr) -> bool {
+        self.display_addresses().iter().any(|interface| interface.ip == ip)
+    }
+
+    pub fn display_addresses(&self) -> Vec<NetworkInterface> {
+        match &self.selection {
+            InterfaceSelection::Automatic => self.automatic
+                .map(|ip| self.interfaces.iter()
+                    .find(|interface| interface.ip == ip)
+                    .cloned()
+                    .unwrap_or(NetworkInterface { name: String::new(), ip, scope_id: 0 }))
+                .into_iter()
+                .collect(),
+            InterfaceSelection::All => self.interfaces.clone(),
+            InterfaceSelection::Selected(selected) => self.interfaces.iter()
+                .filter(|interface| selected.contains(&interface.ip))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn bind_addresses(&self, port: u16) -> Vec<SocketAddr> {
+        if self.selection == InterfaceSelection::All {
+            return vec![
+                SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port),
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port),
+            ];
+        }
+
+        let mut addresses = self.display_addresses()
+            .iter()
+            .map(|interface| interface.socket_addr(port))
+            .collect::<Vec<_>>();
+        addresses.sort();
+        addresses.dedup();
+        addresses
+    }
+
+    /// Socket address of a local ip including the scope id a link-local address needs.
+    pub fn socket_addr(&self, ip: IpAddr, port: u16) -> SocketAddr {
+        self.interfaces.iter()
+            .find(|interface| interface.ip == ip)
+            .map(|interface| interface.socket_addr(port))
+            .unwrap_or_else(|| SocketAddr::new(ip, port))
+    }
+
+    pub fn local_addresses(&self) -> Vec<IpAddr> {
+        self.interfaces.iter().map(|interface| interface.ip).collect()
+    }
+}
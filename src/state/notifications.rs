@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    NewClient,
+    DownloadCompleted,
+    AllFilesDownloaded,
+    ZipFinished,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    pub new_client: bool,
+    pub download_completed: bool,
+    pub all_files_downloaded: bool,
+    pub zip_finished: bool,
+}
+
+impl NotificationSettings {
+    pub fn get(&self, event: NotificationEvent) -> bool {
+        match event {
+            NotificationEvent::NewClient => self.new_client,
+            NotificationEvent::DownloadCompleted => self.download_completed,
+            NotificationEvent::AllFilesDownloaded => self.all_files_downloaded,
+            NotificationEvent::ZipFinished => self.zip_finished,
+        }
+    }
+
+    pub fn set(&mut self, event: NotificationEvent, enabled: bool) {
+        match event {
+            NotificationEvent::NewClient => self.new_client = enabled,
+            NotificationEvent::DownloadCompleted => self.download_completed = enabled,
+            NotificationEvent::AllFilesDownloaded => self.all_files_downloaded = enabled,
+            NotificationEvent::ZipFinished => self.zip_finished = enabled,
+        }
+    }
+}
+
+pub fn show_notification(summary: String, body: String) {
+    std::thread::spawn(move || {
+        let _ = notify_rust::Notification::new()
+            .appname("Fileshare")
+            .summary(&summary)
+            .body(&body)
+            .show();
+    });
+}
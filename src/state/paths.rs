@@ -0,0 +1,179 @@
+use std::{path::{Path, PathBuf}, sync::OnceLock};
+use directories::ProjectDirs;
+
+pub const PORTABLE_MARKER: &str = "portable";
+const MIGRATED_FILES: [(&str, Location); 3] = [
+    ("config.json", Location::Config),
+    ("audit.log", Location::Data),
+    ("access.log", Location::Data),
+];
+
+static PATHS: OnceLock<AppPaths> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Config,
+    Data,
+}
+
+#[derive(Debug, Clone)]
+pub struct AppPaths {
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub cache: PathBuf,
+    pub temp: PathBuf,
+    pub portable: bool,
+}
+
+impl AppPaths {
+    fn resolve() -> Self {
+        let portable_dir = portable_dir();
+        if let Some(dir) = portable_dir.as_ref().filter(|dir| dir.join(PORTABLE_MARKER).is_file()) {
+            return Self::in_dir(dir, true);
+        }
+        // Without a home directory there is nowhere else to go, so behave like a portable install.
+        Self::platform().unwrap_or_else(|| Self::in_dir(&portable_dir.unwrap_or_else(|| PathBuf::from(".")), false))
+    }
+
+    fn platform() -> Option<Self> {
+        ProjectDirs::from("", "", "Fileshare").map(|dirs| Self {
+            config: dirs.config_dir().to_path_buf(),
+            data: dirs.data_dir().to_path_buf(),
+            cache: dirs.cache_dir().to_path_buf(),
+            temp: dirs.cache_dir().join("temp"),
+            portable: false,
+        })
+    }
+
+    fn in_dir(dir: &Path, portable: bool) -> Self {
+        Self {
+            config: dir.join("config"),
+            data: dir.join("data"),
+            cache: dir.join("cache"),
+            temp: dir.join("cache").join("temp"),
+            portable,
+        }
+    }
+
+    fn location(&self, location: Location) -> &Path {
+        match location {
+            Location::Config => &self.config,
+            Location::Data => &self.data,
+        }
+    }
+
+    /// Copies config and logs from older versions or the other install mode unless a config already exists.
+    fn migrate(&self, previous_locations: Vec<AppPaths>) {
+        if self.config.join("config.json").exists() {
+            return;
+        }
+        let previous = previous_locations.into_iter()
+            .find(|paths| paths.config.join("config.json").is_file() && paths.config != self.config);
+        let Some(previous) = previous else {
+            return;
+        };
+        for (file, location) in MIGRATED_FILES {
+            let source = previous.location(location).join(file);
+            let target = self.location(location).join(file);
+            if source.is_file() && !target.exists() && std::fs::create_dir_all(self.location(location)).is_ok() {
+                let _ = std::fs::copy(&source, &target);
+            }
+        }
+    }
+}
+
+pub fn paths() -> &'static AppPaths {
+    PATHS.get_or_init(|| {
+        let paths = AppPaths::resolve();
+        paths.migrate(previous_locations());
+        paths
+    })
+}
+
+fn portable_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+fn previous_locations() -> Vec<AppPaths> {
+    let legacy = |dir: PathBuf| AppPaths {
+        config: dir.clone(),
+        data: dir,
+        cache: PathBuf::new(),
+        temp: PathBuf::new(),
+        portable: false,
+    };
+    let mut locations = vec![legacy(PathBuf::from("./config"))];
+    if let Some(appdata) = std::env::var_os("APPDATA") {
+        locations.push(legacy(PathBuf::from(appdata).join("Fileshare")));
+    }
+    locations.extend(AppPaths::platform());
+    if let Some(dir) = portable_dir() {
+        locations.push(AppPaths::in_dir(&dir, true));
+    }
+    locations
+}
+
+pub fn portable_marker_exists() -> bool {
+    portable_dir().is_some_and(|dir| dir.join(PORTABLE_MARKER).is_file())
+}
+
+pub fn set_portable(portable: bool) -> std::io::Result<()> {
+    let dir = portable_dir().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "executable directory not found"))?;
+    let marker = dir.join(PORTABLE_MARKER);
+    match portable {
+        true => std::fs::write(marker, ""),
+        false if marker.exists() => std::fs::remove_file(marker),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fileshare-paths-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn legacy(dir: &Path) -> AppPaths {
+        AppPaths { config: dir.to_path_buf(), data: dir.to_path_buf(), cache: PathBuf::new(), temp: PathBuf::new(), portable: false }
+    }
+
+    #[test]
+    fn config_and_logs_are_copied_from_the_first_previous_location() {
+        let dir = temp_dir("copy");
+        let old = dir.join("old");
+        std::fs::create_dir_all(&old).unwrap();
+        std::fs::write(old.join("config.json"), "{}").unwrap();
+        std::fs::write(old.join("audit.log"), "audit").unwrap();
+
+        let paths = AppPaths::in_dir(&dir.join("new"), false);
+        paths.migrate(vec![legacy(&dir.join("missing")), legacy(&old)]);
+
+        assert_eq!(std::fs::read_to_string(paths.config.join("config.json")).unwrap(), "{}");
+        assert_eq!(std::fs::read_to_string(paths.data.join("audit.log")).unwrap(), "audit");
+        assert!(!paths.data.join("access.log").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn an_existing_config_is_never_replaced() {
+        let dir = temp_dir("existing");
+        let old = dir.join("old");
+        std::fs::create_dir_all(&old).unwrap();
+        std::fs::write(old.join("config.json"), "old").unwrap();
+        std::fs::write(old.join("audit.log"), "old").unwrap();
+
+        let paths = AppPaths::in_dir(&dir.join("new"), false);
+        std::fs::create_dir_all(&paths.config).unwrap();
+        std::fs::write(paths.config.join("config.json"), "new").unwrap();
+        paths.migrate(vec![legacy(&old)]);
+
+        assert_eq!(std::fs::read_to_string(paths.config.join("config.json")).unwrap(), "new");
+        assert!(!paths.data.join("audit.log").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::server::rate_limiter::Limits;
+
+use super::{access_manager::AccessRules, file_manager::SharedEntry, network_manager::InterfaceSelection, state::default_true};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub port: u16,
+    pub theme: usize,
+    #[serde(default)]
+    pub interfaces: InterfaceSelection,
+    #[serde(default)]
+    pub access_rules: AccessRules,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default = "default_true")]
+    pub block_external_connections: bool,
+    #[serde(default)]
+    pub ask_new_clients: bool,
+    #[serde(default)]
+    pub shared: Vec<SharedEntry>,
+}
+
+impl Profile {
+    pub fn export(profiles: &[Profile], path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(profiles)?)
+    }
+
+    pub fn import(path: &Path) -> Result<Vec<Profile>, String> {
+        let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        // Accept both a single exported profile and a list of them.
+        serde_json::from_str::<Vec<Profile>>(&content)
+            .or_else(|_| serde_json::from_str::<Profile>(&content).map(|profile| vec![profile]))
+            .map_err(|err| err.to_string())
+    }
+}
+
+pub fn upsert(profiles: &mut Vec<Profile>, profile: Profile) {
+    match profiles.iter_mut().find(|existing| existing.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+}
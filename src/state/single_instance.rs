@@ -0,0 +1,151 @@
+use std::{io::{BufRead, BufReader, Write}, path::PathBuf, sync::atomic::{AtomicBool, Ordering}, time::Duration};
+use futures::{channel::mpsc::Sender, SinkExt};
+use iced::{stream::channel, Subscription};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::control::{error, load_or_create_token, read_token, ControlCommand, Request, Responder};
+
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Set once this instance owns the socket, so exiting never removes another instance's socket.
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+#[cfg(not(target_os = "windows"))]
+fn socket_path() -> PathBuf {
+    super::paths::paths().cache.join("instance.sock")
+}
+
+#[cfg(target_os = "windows")]
+fn pipe_name() -> String {
+    format!(r"\\.\pipe\fileshare-{}", std::env::var("USERNAME").unwrap_or_default())
+}
+
+pub fn cli_paths() -> Vec<PathBuf> {
+    std::env::args_os()
+        .skip(1)
+        .map(PathBuf::from)
+        .filter_map(|path| std::path::absolute(&path).ok())
+        .collect()
+}
+
+/// Hands the paths to an already running instance. Returns false if there is none.
+pub fn forward_to_running(paths: &[PathBuf]) -> bool {
+    let Some(token) = read_token() else {
+        return false;
+    };
+    send(&Request { token, command: ControlCommand::Open { paths: paths.to_vec() } }).is_ok()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn connect() -> std::io::Result<std::os::unix::net::UnixStream> {
+    let stream = std::os::unix::net::UnixStream::connect(socket_path()).inspect_err(|err| {
+        // Nobody is listening, so a socket file left behind by a crashed instance is stale.
+        if err.kind() == std::io::ErrorKind::ConnectionRefused {
+            let _ = std::fs::remove_file(socket_path());
+        }
+    })?;
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+    Ok(stream)
+}
+
+#[cfg(target_os = "windows")]
+fn connect() -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new().read(true).write(true).open(pipe_name())
+}
+
+pub fn send(request: &Request) -> Result<Value, String> {
+    let mut stream = connect().map_err(|_| "Fileshare is not running".to_string())?;
+    let mut payload = serde_json::to_vec(request).map_err(|err| err.to_string())?;
+    payload.push(b'\n');
+    stream.write_all(&payload).map_err(|err| err.to_string())?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).map_err(|err| err.to_string())?;
+    serde_json::from_str(&line).map_err(|err| err.to_string())
+}
+
+/// Yields the commands sent by other instances and `ctl`, or an error if the socket couldn't be claimed.
+pub fn listen() -> Subscription<Result<(ControlCommand, Responder), String>> {
+    Subscription::run(|| channel(10, |mut tx: Sender<Result<(ControlCommand, Responder), String>>| async move {
+        let token = load_or_create_token();
+        #[cfg(not(target_os = "windows"))]
+        {
+            let path = socket_path();
+            let _ = std::fs::create_dir_all(path.parent().unwrap());
+            // Only a socket nobody answers on is stale, a live one belongs to an instance that started at the same time.
+            match tokio::net::UnixStream::connect(&path).await {
+                Ok(_) => {
+                    let _ = tx.send(Err("another instance is already running".to_string())).await;
+                    return;
+                },
+                Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused => {
+                    let _ = std::fs::remove_file(&path);
+                },
+                Err(_) => {},
+            }
+            let listener = match tokio::net::UnixListener::bind(&path) {
+                Ok(listener) => listener,
+                Err(err) => {
+                    let _ = tx.send(Err(err.to_string())).await;
+                    return;
+                },
+            };
+            LISTENING.store(true, Ordering::Relaxed);
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, token.clone(), tx.clone()));
+            }
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use tokio::net::windows::named_pipe::ServerOptions;
+            let mut server = match ServerOptions::new().first_pipe_instance(true).create(pipe_name()) {
+                Ok(server) => server,
+                Err(err) => {
+                    let _ = tx.send(Err(err.to_string())).await;
+                    return;
+                },
+            };
+            while server.connect().await.is_ok() {
+                let Ok(next) = ServerOptions::new().create(pipe_name()) else {
+                    return;
+                };
+                let stream = std::mem::replace(&mut server, next);
+                tokio::spawn(handle(stream, token.clone(), tx.clone()));
+            }
+        }
+    }))
+}
+
+async fn handle(mut stream: impl AsyncRead + AsyncWrite + Unpin, token: String, mut tx: Sender<Result<(ControlCommand, Responder), String>>) {
+    let mut line = String::new();
+    // A client that connects and never finishes its line must not hold on to the connection forever.
+    let read = tokio::time::timeout(REQUEST_TIMEOUT, tokio::io::BufReader::new(&mut stream).read_line(&mut line)).await;
+    if !matches!(read, Ok(Ok(_))) {
+        return;
+    }
+    let response = match serde_json::from_str::<Request>(&line) {
+        Err(err) => error(err),
+        Ok(request) if request.token != token => error("Invalid token"),
+        Ok(request) => {
+            let (responder, rx) = Responder::new();
+            let _ = tx.send(Ok((request.command, responder))).await;
+            match tokio::time::timeout(RESPONSE_TIMEOUT, rx).await {
+                Ok(Ok(response)) => response,
+                _ => error("No response from Fileshare"),
+            }
+        },
+    };
+    let _ = stream.write_all(format!("{}\n", response).as_bytes()).await;
+    let _ = stream.flush().await;
+    // Closing a pipe before the client has read the reply can discard it, so wait for the client to hang up.
+    let _ = tokio::time::timeout(RESPONSE_TIMEOUT, stream.read(&mut [0; 1])).await;
+}
+
+pub fn release() {
+    #[cfg(not(target_os = "windows"))]
+    if LISTENING.load(Ordering::Relaxed) {
+        let _ = std::fs::remove_file(socket_path());
+    }
+}
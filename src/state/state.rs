@@ -1,25 +1,86 @@
-use std::{fs::{read_to_string, File}, io::Write, net::{IpAddr, Ipv4Addr}, sync::{atomic::AtomicBool, Arc}, vec};
-use local_ip_address::local_ip;
+use std::{collections::VecDeque, fs::{read_to_string, File}, io::Write, net::{IpAddr, Ipv4Addr, SocketAddr}, sync::{atomic::AtomicBool, Arc}, vec};
 use iced::widget;
 use qrcode_generator::QrCodeEcc;
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
 
-use crate::{state::{client_manager::ClientManager, file_manager::FileManager, theme_selector::ThemeSelector}, views::{language::Language, root_view::CONNECTION_PANE_WIDTH}};
+use crate::{server::{access_log::{AccessLog, AccessLogConfig}, benchmark::BenchmarkResult, metrics::{Metrics, MetricsConfig}, connection_tracker::ConnectionTracker, event_bus::ProgressRegistry, mdns::{DiscoveredInstance, Mdns}, rate_limiter::{Limits, RateLimiter}, router::normalize_base_path}, state::{access_manager::{AccessManager, AccessRules}, audit_log::{AuditEvent, AuditLog}, client_manager::{ClientKey, ClientManager}, file_manager::{FileManager, SharedEntry}, hooks::{Hook, HookEvent, HookKind, HookResult}, invite_manager::{Invite, InviteExpiry, InviteManager}, network_manager::{InterfaceSelection, NetworkManager}, notifications::NotificationSettings, paths::{paths, portable_marker_exists}, profiles::Profile, theme_selector::ThemeSelector}, views::{language::Language, root_view::CONNECTION_PANE_WIDTH}};
+
+pub const MAX_HOOK_RESULTS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerStatus {
+    Stopped,
+    Starting,
+    Running(Vec<SocketAddr>),
+    BindFailed { port: u16, error: String },
+}
 
 pub struct State {
     pub theme: ThemeSelector,
     pub client_manager: ClientManager,
+    pub expanded_client: Option<ClientKey>,
+    pub resolve_hostnames: bool,
+    pub notifications: NotificationSettings,
+    pub hooks: Vec<Hook>,
+    pub hook_results: VecDeque<HookResult>,
+    pub hook_event: HookEvent,
+    pub hook_kind: HookKind,
+    pub hook_buffer: String,
     pub file_manager: FileManager,
+    pub restore_shared: bool,
+    pub pending_restore: Vec<SharedEntry>,
+    pub profiles: Vec<Profile>,
+    pub active_profile: Option<String>,
+    pub profile_name_buffer: String,
+    pub profile_error: Option<String>,
+    pub portable: bool,
+    pub storage_error: Option<String>,
+    pub instance_error: Option<String>,
+    pub invite_manager: InviteManager,
+    pub invite_name_buffer: String,
+    pub invite_expiry: InviteExpiry,
+    pub selected_invite: Option<String>,
+    pub editing_invite: Option<String>,
+    pub network_manager: NetworkManager,
+    pub bound_addresses: Vec<SocketAddr>,
     pub ip_adress: Option<IpAddr>,
     pub ip_adress_public: Option<IpAddr>,
     pub port: u16,
     pub qr_code: widget::image::Handle,
     pub server_handle: Option<iced::task::Handle>,
+    pub server_shutdown: Option<CancellationToken>,
+    pub server_generation: u64,
+    pub server_status: ServerStatus,
+    pub port_fallback: bool,
+    pub benchmark: Option<Result<BenchmarkResult, String>>,
+    pub benchmark_running: bool,
     pub port_buffer: String,
     pub local_host: bool,
     pub size: (f32, f32),
     pub show_connections: bool,
     pub block_external_connections: Arc<AtomicBool>,
+    pub access_manager: AccessManager,
+    pub rate_limiter: RateLimiter,
+    pub access_log: AccessLog,
+    pub metrics: Metrics,
+    pub metrics_port_buffer: String,
+    pub metrics_error: Option<String>,
+    pub connections: ConnectionTracker,
+    pub progress: ProgressRegistry,
+    pub mdns: Mdns,
+    pub nearby_shares: Vec<DiscoveredInstance>,
+    pub allowlist_buffer: String,
+    pub denylist_buffer: String,
+    pub proxy_buffer: String,
+    pub base_path: String,
+    pub base_path_buffer: String,
+    pub show_settings: bool,
+    pub show_history: bool,
+    pub audit_log: AuditLog,
+    pub history_error: Option<String>,
+    pub history_filter: String,
+    pub history_event: Option<AuditEvent>,
     pub show_qr_code: bool,
     pub language: Language,
     pub ignore_hidden: bool,
@@ -28,9 +89,10 @@ pub struct State {
 
 impl Default for State {
     fn default() -> Self {
-        let ip = local_ip().ok();
         let ip_public = public_ip_address::perform_lookup(None).map(|lookup|lookup.ip).ok();
-        let config_path = format!("{}/config.json", config_path());
+        let paths = paths();
+        let config_path = paths.config.join("config.json");
+        let audit_path = paths.data.join("audit.log");
         
         let mut theme = ThemeSelector::new();
         let mut port = 8080;
@@ -38,6 +100,28 @@ impl Default for State {
         let mut show_qr_code = true;
         let mut port_buffer = "8080".to_string();
         let mut language = Language::English;
+        let mut access_rules = AccessRules::default();
+        let mut limits = Limits::default();
+        let mut base_path = String::new();
+        let mut interfaces = InterfaceSelection::default();
+        let mut port_fallback = false;
+        let mut access_log = AccessLogConfig::default();
+        let mut metrics = MetricsConfig::default();
+        let mut resolve_hostnames = false;
+        let mut notifications = NotificationSettings::default();
+        let mut hooks = Vec::new();
+        let mut restore_shared = false;
+        let mut shared = Vec::new();
+        let mut profiles = Vec::new();
+        let mut active_profile = None;
+        let mut ignore_hidden = true;
+        let mut use_gitignore = true;
+        let mut block_external_connections = true;
+        let mut ask_new_clients = false;
+        let mut invites = Vec::new();
+        let mut require_invite = false;
+        let mut local_host = true;
+        let mut display_ip = None;
         
         if let Ok(file) = read_to_string(config_path) {
             let json = serde_json::from_str::<PersistantState>(&file);
@@ -48,29 +132,112 @@ impl Default for State {
                 show_connections = data.show_connections;
                 show_qr_code = data.show_qr_code;
                 language = data.language;
+                access_rules = data.access_rules;
+                limits = data.limits;
+                base_path = normalize_base_path(&data.base_path);
+                interfaces = data.interfaces;
+                port_fallback = data.port_fallback;
+                access_log = data.access_log;
+                metrics = data.metrics;
+                resolve_hostnames = data.resolve_hostnames;
+                notifications = data.notifications;
+                hooks = data.hooks;
+                restore_shared = data.restore_shared;
+                shared = data.shared;
+                profiles = data.profiles;
+                active_profile = data.active_profile;
+                ignore_hidden = data.ignore_hidden;
+                use_gitignore = data.use_gitignore;
+                block_external_connections = data.block_external_connections;
+                ask_new_clients = data.ask_new_clients;
+                invites = data.invites;
+                require_invite = data.require_invite;
+                local_host = data.local_host || ip_public.is_none();
+                display_ip = data.display_ip;
             }   
         }
+
+        let network_manager = NetworkManager::new(interfaces);
+        let displayed = network_manager.display_addresses();
+        let ip = displayed.iter()
+            .find(|interface| Some(interface.ip) == display_ip)
+            .or(displayed.first())
+            .map(|interface| interface.ip);
         
-        let qr_code = Self::create_qr_code(&Self::url_string(&ip.unwrap_or(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))), port));
+        let qr_code = match local_host {
+            true => Self::create_qr_code(&Self::url_string(network_manager.socket_addr(ip.unwrap_or(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))), port), &base_path)),
+            false => Self::create_qr_code(&Self::url_string(SocketAddr::new(ip_public.unwrap(), port), &base_path)),
+        };
         
         Self {
             theme,
             client_manager: ClientManager::default(),
+            expanded_client: None,
+            resolve_hostnames,
+            notifications,
+            hooks,
+            hook_results: VecDeque::with_capacity(MAX_HOOK_RESULTS),
+            hook_event: HookEvent::Downloaded,
+            hook_kind: HookKind::Command,
+            hook_buffer: String::new(),
+            bound_addresses: network_manager.bind_addresses(port),
+            network_manager,
             ip_adress: ip,
             ip_adress_public: ip_public,
             port,
             file_manager: FileManager::new(),
+            restore_shared,
+            pending_restore: if restore_shared { shared } else { Vec::new() },
+            profiles,
+            active_profile,
+            profile_name_buffer: String::new(),
+            profile_error: None,
+            portable: portable_marker_exists(),
+            storage_error: None,
+            instance_error: None,
+            invite_manager: InviteManager::new(invites, require_invite),
+            invite_name_buffer: String::new(),
+            invite_expiry: InviteExpiry::default(),
+            selected_invite: None,
+            editing_invite: None,
             qr_code,
             server_handle: None,
+            server_shutdown: None,
+            server_generation: 0,
+            server_status: ServerStatus::Stopped,
+            port_fallback,
+            benchmark: None,
+            benchmark_running: false,
             port_buffer,
-            local_host: true,
+            local_host,
             size: (0.0, 0.0),
             show_connections,
-            block_external_connections: Arc::new(AtomicBool::new(true)),
+            block_external_connections: Arc::new(AtomicBool::new(block_external_connections)),
+            access_manager: AccessManager::new(access_rules, ask_new_clients),
+            rate_limiter: RateLimiter::new(limits),
+            access_log: AccessLog::new(paths.data.join("access.log"), access_log),
+            metrics: Metrics::new(metrics),
+            metrics_port_buffer: metrics.port.map(|port| port.to_string()).unwrap_or_default(),
+            metrics_error: None,
+            connections: ConnectionTracker::default(),
+            progress: ProgressRegistry::default(),
+            mdns: Mdns::new(),
+            nearby_shares: Vec::new(),
+            allowlist_buffer: String::new(),
+            denylist_buffer: String::new(),
+            proxy_buffer: String::new(),
+            base_path_buffer: base_path.clone(),
+            base_path,
+            show_settings: false,
+            show_history: false,
+            audit_log: AuditLog::new(audit_path),
+            history_error: None,
+            history_filter: String::new(),
+            history_event: None,
             show_qr_code,
             language,
-            ignore_hidden: true,
-            use_gitignore: true,
+            ignore_hidden,
+            use_gitignore,
         }
     }
 }
@@ -81,19 +248,89 @@ struct PersistantState {
     port: u16,
     show_connections: bool,
     show_qr_code: bool,
-    language: Language
+    language: Language,
+    #[serde(default)]
+    access_rules: AccessRules,
+    #[serde(default)]
+    limits: Limits,
+    #[serde(default)]
+    base_path: String,
+    #[serde(default)]
+    interfaces: InterfaceSelection,
+    #[serde(default)]
+    port_fallback: bool,
+    #[serde(default)]
+    access_log: AccessLogConfig,
+    #[serde(default)]
+    metrics: MetricsConfig,
+    #[serde(default)]
+    resolve_hostnames: bool,
+    #[serde(default)]
+    notifications: NotificationSettings,
+    #[serde(default)]
+    hooks: Vec<Hook>,
+    #[serde(default)]
+    restore_shared: bool,
+    #[serde(default)]
+    shared: Vec<SharedEntry>,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    #[serde(default)]
+    active_profile: Option<String>,
+    #[serde(default = "default_true")]
+    ignore_hidden: bool,
+    #[serde(default = "default_true")]
+    use_gitignore: bool,
+    #[serde(default = "default_true")]
+    block_external_connections: bool,
+    #[serde(default)]
+    ask_new_clients: bool,
+    #[serde(default)]
+    invites: Vec<Invite>,
+    #[serde(default)]
+    require_invite: bool,
+    #[serde(default = "default_true")]
+    local_host: bool,
+    #[serde(default)]
+    display_ip: Option<IpAddr>,
+}
+
+pub fn default_true() -> bool {
+    true
 }
 
 impl State {
     pub fn create_url_string(&self) -> String {
-        if self.local_host {
-            return Self::url_string(&self.ip_adress.unwrap_or(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))), self.port);
+        let url = if self.local_host {
+            Self::url_string(self.network_manager.socket_addr(self.ip_adress.unwrap_or(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))), self.port), &self.base_path)
+        } else {
+            Self::url_string(SocketAddr::new(self.ip_adress_public.unwrap(), self.port), &self.base_path)
+        };
+        match &self.selected_invite {
+            Some(token) => format!("{}?invite={}", url, token),
+            None => url,
         }
-        Self::url_string(&self.ip_adress_public.unwrap(), self.port)
     }
 
-    fn url_string(ip: &IpAddr, port: u16) -> String {
-        format!("http://{}:{}/index", ip, port)
+    pub fn create_local_name_url_string(&self) -> Option<String> {
+        if !self.local_host || !self.mdns.is_advertising() {
+            return None;
+        }
+        let url = format!("http://{}:{}{}/index", self.mdns.hostname(), self.port, self.base_path);
+        match &self.selected_invite {
+            Some(token) => Some(format!("{}?invite={}", url, token)),
+            None => Some(url),
+        }
+    }
+
+    fn url_string(addr: SocketAddr, base_path: &str) -> String {
+        let host = match addr {
+            // The zone of a link-local address has to be percent-encoded in a URL (RFC 6874).
+            SocketAddr::V6(addr) if addr.scope_id() != 0 => format!("[{}%25{}]", addr.ip(), addr.scope_id()),
+            SocketAddr::V6(addr) => format!("[{}]", addr.ip()),
+            SocketAddr::V4(addr) => addr.ip().to_string(),
+        };
+        format!("http://{}:{}{}/index", host, addr.port(), base_path)
     }
 
     pub fn create_qr_code(url: &String) -> widget::image::Handle {
@@ -113,28 +350,60 @@ impl State {
             port: self.port,
             show_connections: self.show_connections,
             show_qr_code: self.show_qr_code,
-            language: self.language
+            language: self.language,
+            access_rules: self.access_manager.rules(),
+            limits: self.rate_limiter.limits(),
+            base_path: self.base_path.clone(),
+            interfaces: self.network_manager.selection().clone(),
+            port_fallback: self.port_fallback,
+            access_log: self.access_log.config(),
+            metrics: self.metrics.config(),
+            resolve_hostnames: self.resolve_hostnames,
+            notifications: self.notifications,
+            hooks: self.hooks.clone(),
+            restore_shared: self.restore_shared,
+            shared: match self.restore_shared {
+                true => self.file_manager.shared_entries(),
+                false => Vec::new(),
+            },
+            profiles: self.profiles.clone(),
+            active_profile: self.active_profile.clone(),
+            ignore_hidden: self.ignore_hidden,
+            use_gitignore: self.use_gitignore,
+            block_external_connections: self.block_external_connections.load(std::sync::atomic::Ordering::Relaxed),
+            ask_new_clients: self.access_manager.ask_new_clients(),
+            invites: self.invite_manager.get_view(),
+            require_invite: self.invite_manager.require_invite(),
+            local_host: self.local_host,
+            display_ip: self.ip_adress,
         };
-        let config_path = config_path();
+        let config_path = &paths().config;
         let json = serde_json::to_string(&persistant_state).unwrap();
         
-        let _ = std::fs::create_dir_all(&config_path);
+        let _ = std::fs::create_dir_all(config_path);
 
-        if let Ok(mut file) = File::create(format!("{}/config.json",config_path)) {
+        if let Ok(mut file) = File::create(config_path.join("config.json")) {
             let _ = file.write_all(json.as_bytes());
         }
     }
 }
 
-fn config_path() -> String {
-    #[cfg(feature = "appdata")]
-    {
-        let appdata_path = std::env::var("APPDATA").unwrap_or_else(|_| String::from("./appdata/config"));
-        return format!("{}/Fileshare", appdata_path);
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddrV4, SocketAddrV6};
+    use super::*;
+
+    #[test]
+    fn urls_keep_the_scope_of_link_local_addresses() {
+        let addr = SocketAddr::V6(SocketAddrV6::new("fe80::1".parse().unwrap(), 8080, 0, 3));
+        assert_eq!(State::url_string(addr, ""), "http://[fe80::1%253]:8080/index");
     }
-    #[cfg(not(feature = "appdata"))]
-    {
-        return String::from("./config");
+
+    #[test]
+    fn urls_without_scope() {
+        let addr = SocketAddr::V6(SocketAddrV6::new("2001:db8::1".parse().unwrap(), 8080, 0, 0));
+        assert_eq!(State::url_string(addr, "/share"), "http://[2001:db8::1]:8080/share/index");
+        let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 2), 80));
+        assert_eq!(State::url_string(addr, ""), "http://192.168.1.2:80/index");
     }
 }
-
@@ -1,5 +1,7 @@
 use iced::keyboard::key::Named;
 use iced::{keyboard, window, Subscription};
+use crate::server::mdns;
+use crate::state::single_instance;
 use crate::state::state::State;
 
 use crate::state::update::Message;
@@ -8,13 +10,20 @@ pub fn subscription(state: &State) -> Subscription<Message> {
     let keyboard = keyboard_input(state);
     let window = window_events();
     let refresh_loop = iced::time::every(std::time::Duration::from_secs(1)).map(|_| Message::Refresh);
-    let update_loop = iced::time::every(std::time::Duration::from_millis(200)).map(|_| Message::None);
+    let update_loop = iced::time::every(std::time::Duration::from_millis(200)).map(|_| Message::UpdateProgress);
+    let discovery = mdns::discovery().map(Message::MdnsMessage);
+    let instance = single_instance::listen().map(|event| match event {
+        Ok((command, responder)) => Message::Control(command, responder),
+        Err(error) => Message::InstanceListenFailed(error),
+    });
 
     Subscription::batch([
         keyboard, 
         window,
         refresh_loop,
-        update_loop
+        update_loop,
+        discovery,
+        instance,
     ])
 }
 
@@ -10,6 +10,9 @@ pub struct ThemeSelector {
 
 impl ThemeSelector {
     pub fn set_indx(&mut self, indx: usize) {
+        if indx >= self.themes.len() {
+            return;
+        }
         self.indx = indx;
         self.current.write().unwrap().clone_from(&self.themes[self.indx]);
     }
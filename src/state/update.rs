@@ -1,21 +1,27 @@
-use std::{net::IpAddr, path::PathBuf, process::Command, thread::sleep};
+use std::{net::{IpAddr, SocketAddr}, path::{Path, PathBuf}, process::Command};
 use copypasta::{ClipboardContext, ClipboardProvider};
 use ignore::WalkBuilder;
-use local_ip_address::local_ip;
+use ipnet::IpNet;
 use rfd::FileDialog;
+use futures::StreamExt;
 use iced::{stream::channel, window::Event, Size, Task};
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
 
-use crate::{server::router::server, state::{file_manager::FileInfo, state::State}, views::language::Language};
+use crate::{server::{access_log::{AccessLogConfig, LogRotation}, benchmark::{run_benchmark, BenchmarkResult}, mdns::MdnsMessage, metrics::MetricsConfig, rate_limiter::LimitKind, router::{normalize_base_path, server, ServerState}}, state::{access_manager::{parse_rule, AccessList, Approval}, audit_log::{AuditEntry, AuditEvent, ExportFormat}, client_manager::{ClientId, ClientKey, TransferState}, control::{error, ok, qr_text, ControlCommand, Responder}, file_manager::FileInfo, hooks::{run_hook, Hook, HookContext, HookEvent, HookKind, HookResult}, invite_manager::InviteExpiry, network_manager::{InterfaceSelection, NetworkInterface}, notifications::{show_notification, NotificationEvent}, paths::set_portable, profiles::{upsert, Profile}, state::{ServerStatus, State, MAX_HOOK_RESULTS}}, views::language::Language};
 
-use super::file_manager::{FileManager, ZipMessage};
+use super::file_manager::{FileManager, SharedEntry, ZipMessage};
 
 #[derive(Debug, Clone)]
 pub enum ServerMessage {
-    Downloaded { index: usize , ip: IpAddr },
-    ClientConnected { ip: IpAddr },
-    DownloadActive { ip: IpAddr, num_bytes: usize },
-    DownloadRequest { index: usize, ip: IpAddr },
-    DownloadAllRequest { ip: IpAddr },
+    Downloaded { index: usize , client: ClientId, bytes: usize },
+    DownloadAborted { index: usize, client: ClientId, bytes: usize },
+    ClientConnected { client: ClientId, user_agent: Option<String>, name: Option<String> },
+    DownloadRequest { index: usize, client: ClientId },
+    DownloadAllRequest { client: ClientId },
+    Started { generation: u64, addresses: Vec<SocketAddr> },
+    BindFailed { generation: u64, port: u16, error: String },
+    MetricsBindFailed { generation: u64, port: u16, error: String },
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +32,9 @@ pub enum Message {
     NextTheme,
     PreviousTheme,
     CopyUrl,
+    CopyLocalNameUrl,
+    OpenUrl(String),
+    MdnsMessage(MdnsMessage),
     None,
     OpenInBrowser,
     DeleteFile(usize),
@@ -39,8 +48,49 @@ pub enum Message {
     ChangePort,
     PortTextUpdate(String),
     ToggleConnectionsView,
+    PortFallback(bool),
+    RunBenchmark,
+    BenchmarkDone(Result<BenchmarkResult, String>),
+    InterfaceSelectionChanged(InterfaceSelection),
+    ToggleInterface(IpAddr),
+    DisplayInterfaceChanged(NetworkInterface),
     BlockExternalConnections(bool),
+    AskNewClients(bool),
+    ApproveClient(IpAddr),
+    DenyClient(IpAddr),
+    DisconnectClient(IpAddr),
+    BlockClient(IpAddr),
+    TrustClient(IpAddr),
+    ToggleSettings,
+    ToggleClientDetails(ClientKey),
+    ResolveHostnames(bool),
+    NotificationToggled(NotificationEvent, bool),
+    HookEventSelected(HookEvent),
+    HookKindSelected(HookKind),
+    HookInputUpdate(String),
+    AddHook,
+    RemoveHook(usize),
+    HookFinished(HookResult),
+    HostnameResolved(IpAddr, Option<String>),
+    ToggleHistory,
+    HistoryFilterUpdate(String),
+    HistoryEventFilter(Option<AuditEvent>),
+    ExportHistory(ExportFormat),
+    RuleInputUpdate(AccessList, String),
+    AddRule(AccessList),
+    RemoveRule(AccessList, IpNet),
+    LimitChanged(LimitKind, String),
+    AccessLogEnabled(bool),
+    AccessLogRotation(LogRotation),
+    AccessLogMaxSize(String),
+    MetricsEnabled(bool),
+    MetricsLocalOnly(bool),
+    MetricsPortUpdate(String),
+    ChangeMetricsPort,
+    BasePathUpdate(String),
+    ChangeBasePath,
     Refresh,
+    UpdateProgress,
     ShowQrCode(bool),
     WindowEvent(iced::window::Event),
     RetryIp,
@@ -49,16 +99,202 @@ pub enum Message {
     LanguageChanged(Language),
     IgnoreHidden(bool),
     UseGitignore(bool),
+    RestoreShared(bool),
+    RemoveMissing(usize),
+    RetryMissing,
+    ProfileSelected(String),
+    ProfileNameUpdate(String),
+    SaveProfile,
+    DeleteProfile(String),
+    ImportProfiles,
+    ExportProfiles,
+    PortableMode(bool),
+    OpenPaths(Vec<PathBuf>),
+    Control(ControlCommand, Responder),
+    InstanceListenFailed(String),
+    OpenFolder(PathBuf),
+    InviteNameUpdate(String),
+    InviteExpiryChanged(InviteExpiry),
+    CreateInvite,
+    DeleteInvite(String),
+    SelectInvite(Option<String>),
+    EditInviteFiles(Option<String>),
+    ToggleInviteFile(PathBuf),
+    RequireInvite(bool),
 }
 
 pub fn update(state: &mut State, message: Message) -> Task<Message> {
     match message {
         Message::IgnoreHidden(ignore) => {
             state.ignore_hidden = ignore;
+            state.backup_state();
         },
 
         Message::UseGitignore(use_gitignore) => {
             state.use_gitignore = use_gitignore;
+            state.backup_state();
+        },
+
+        Message::RestoreShared(restore) => {
+            state.restore_shared = restore;
+            state.backup_state();
+        },
+
+        Message::RemoveMissing(index) => {
+            state.file_manager.remove_missing(index);
+            state.backup_state();
+        },
+
+        Message::ProfileSelected(name) => {
+            if let Some(profile) = state.profiles.iter().find(|profile| profile.name == name).cloned() {
+                return apply_profile(state, profile);
+            }
+        },
+
+        Message::ProfileNameUpdate(name) => {
+            state.profile_name_buffer = name;
+        },
+
+        Message::SaveProfile => {
+            let name = state.profile_name_buffer.trim().to_string();
+            if name.is_empty() {
+                return Task::none();
+            }
+            let profile = current_profile(state, name.clone());
+            upsert(&mut state.profiles, profile);
+            state.active_profile = Some(name);
+            state.profile_name_buffer.clear();
+            state.backup_state();
+        },
+
+        Message::DeleteProfile(name) => {
+            state.profiles.retain(|profile| profile.name != name);
+            if state.active_profile.as_ref() == Some(&name) {
+                state.active_profile = None;
+            }
+            state.backup_state();
+        },
+
+        Message::ImportProfiles => {
+            let path = FileDialog::new()
+                .add_filter("JSON", &["json"])
+                .pick_file();
+            if let Some(path) = path {
+                match Profile::import(&path) {
+                    Ok(profiles) => {
+                        profiles.into_iter().for_each(|profile| upsert(&mut state.profiles, profile));
+                        state.profile_error = None;
+                        state.backup_state();
+                    },
+                    Err(error) => state.profile_error = Some(state.language.profile_import_failed(error)),
+                }
+            }
+        },
+
+        Message::ExportProfiles => {
+            let path = FileDialog::new()
+                .add_filter("JSON", &["json"])
+                .set_file_name("fileshare-profiles.json")
+                .save_file();
+            if let Some(path) = path {
+                state.profile_error = Profile::export(&state.profiles, &path)
+                    .err()
+                    .map(|error| state.language.profile_export_failed(error));
+            }
+        },
+
+        Message::OpenPaths(paths) => {
+            let paths = paths.into_iter().filter(|path| path.exists()).collect::<Vec<_>>();
+            add_files_from_path_list(state, paths);
+            let focus = iced::window::get_latest().and_then(iced::window::gain_focus);
+            if state.server_handle.is_none() {
+                return Task::batch([start_server(state), focus]);
+            }
+            return focus;
+        },
+
+        Message::InstanceListenFailed(error) => {
+            state.instance_error = Some(state.language.instance_listen_failed(error));
+        },
+
+        Message::Control(command, responder) => {
+            let (response, task) = control(state, command);
+            responder.respond(response);
+            return task;
+        },
+
+        Message::PortableMode(portable) => {
+            match set_portable(portable) {
+                Ok(()) => {
+                    state.portable = portable;
+                    state.storage_error = None;
+                },
+                Err(error) => state.storage_error = Some(state.language.portable_mode_failed(error)),
+            }
+        },
+
+        Message::OpenFolder(path) => {
+            let _ = std::fs::create_dir_all(&path);
+            let _ = open::that(path);
+        },
+
+        Message::RetryMissing => {
+            let entries = state.file_manager.take_missing();
+            return restore_shared_files(state, entries);
+        },
+
+        Message::InviteNameUpdate(name) => {
+            state.invite_name_buffer = name;
+        },
+
+        Message::InviteExpiryChanged(expiry) => {
+            state.invite_expiry = expiry;
+        },
+
+        Message::CreateInvite => {
+            let name = state.invite_name_buffer.trim().to_string();
+            if name.is_empty() {
+                return Task::none();
+            }
+            let token = state.invite_manager.create(name, state.invite_expiry);
+            state.invite_name_buffer.clear();
+            state.selected_invite = Some(token);
+            state.qr_code = State::create_qr_code(&state.create_url_string());
+            state.backup_state();
+        },
+
+        Message::DeleteInvite(token) => {
+            state.invite_manager.remove(&token);
+            if state.editing_invite.as_ref() == Some(&token) {
+                state.editing_invite = None;
+            }
+            if state.selected_invite.as_ref() == Some(&token) {
+                state.selected_invite = None;
+                state.qr_code = State::create_qr_code(&state.create_url_string());
+            }
+            state.backup_state();
+        },
+
+        Message::SelectInvite(token) => {
+            state.selected_invite = token;
+            state.qr_code = State::create_qr_code(&state.create_url_string());
+        },
+
+        Message::EditInviteFiles(token) => {
+            state.editing_invite = token;
+        },
+
+        Message::ToggleInviteFile(path) => {
+            if let Some(token) = &state.editing_invite {
+                let all_files = state.file_manager.get_view().iter().map(|(_, file)| file.path.clone());
+                state.invite_manager.toggle_file(token, path, all_files);
+                state.backup_state();
+            }
+        },
+
+        Message::RequireInvite(require) => {
+            state.invite_manager.set_require_invite(require);
+            state.backup_state();
         },
 
         Message::LanguageChanged(language) => {
@@ -67,10 +303,15 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
         },
 
         Message::ZipMessage(ZipMessage::Done{path}) => {
-            state.file_manager.zip_compressing_done(&path);
+            let zip = state.file_manager.zip_compressing_done(&path);
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            notify(state, NotificationEvent::ZipFinished, state.language.notification_zip_finished_body(name));
+            state.backup_state();
+            let hooks = run_hooks(state, HookEvent::ZipDone, HookContext { file: Some(zip), ..HookContext::default() });
             if state.server_handle.is_none() {
-                return start_server(state);
+                return Task::batch([start_server(state), hooks]);
             }
+            return hooks;
         },
 
         Message::ZipMessage(ZipMessage::Started{path, num_files}) => {
@@ -82,7 +323,8 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
         },
 
         Message::ZipCancel(path) => {
-            state.file_manager.zip_compressing_canceld(&PathBuf::from(path));
+            state.file_manager.zip_compressing_canceld(&path);
+            state.backup_state();
         },
 
         Message::SelectZipExplorer => {
@@ -94,33 +336,33 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
                 if state.file_manager.already_compressed(&path) {
                     return Task::none();
                 }
-
-                let path_clone = path.clone();
-                let use_gitignore = state.use_gitignore;
-                let ignore_hidden = state.ignore_hidden;
-                let stream = channel(100, move |tx: futures::channel::mpsc::Sender<_>| {
-                    let tx = tx.clone();
-                    let path = path.clone();
-                    async move {
-                        FileManager::start_zip_task(path, tx, use_gitignore, ignore_hidden).await;
-                    }
-                });
-            
-                let task = Task::run(stream, |server_message| {
-                    Message::ZipMessage(server_message)
-                });
-            
-                let (task, handle) = Task::abortable(task);
-                state.file_manager.add_new_zip_compressing(path_clone, handle);
+                let task = start_zip(state, path, state.use_gitignore, state.ignore_hidden);
+                state.backup_state();
                 return task;
             }
         },
 
         Message::RetryIp => {
-            state.ip_adress = local_ip().ok();
-            if state.ip_adress.is_some() {
-                state.qr_code = State::create_qr_code(&state.create_url_string());
-            }
+            state.network_manager.refresh();
+            return update_network(state);
+        },
+
+        Message::InterfaceSelectionChanged(selection) => {
+            state.network_manager.set_selection(selection);
+            state.backup_state();
+            return update_network(state);
+        },
+
+        Message::ToggleInterface(ip) => {
+            state.network_manager.toggle(ip);
+            state.backup_state();
+            return update_network(state);
+        },
+
+        Message::DisplayInterfaceChanged(interface) => {
+            state.ip_adress = Some(interface.ip);
+            state.qr_code = State::create_qr_code(&state.create_url_string());
+            state.backup_state();
         },
 
         Message::ThemeChanged(theme) => {
@@ -148,6 +390,149 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
 
         Message::BlockExternalConnections(block) => {
             state.block_external_connections.store(block, std::sync::atomic::Ordering::Relaxed);
+            state.backup_state();
+        },
+
+        Message::AskNewClients(ask) => {
+            state.access_manager.set_ask_new_clients(ask);
+            state.backup_state();
+        },
+
+        Message::ApproveClient(ip) => {
+            state.access_manager.set(ip, Approval::Approved);
+        },
+
+        Message::DenyClient(ip) => {
+            state.access_manager.set(ip, Approval::Denied);
+        },
+
+        Message::DisconnectClient(ip) => {
+            state.access_manager.disconnect(ip);
+        },
+
+        Message::BlockClient(ip) => {
+            state.access_manager.remove_rule(AccessList::Allow, IpNet::from(ip));
+            state.access_manager.add_rule(AccessList::Deny, IpNet::from(ip));
+            state.access_manager.disconnect(ip);
+            state.backup_state();
+        },
+
+        Message::TrustClient(ip) => {
+            state.access_manager.remove_rule(AccessList::Deny, IpNet::from(ip));
+            state.access_manager.add_rule(AccessList::Allow, IpNet::from(ip));
+            state.access_manager.set(ip, Approval::Approved);
+            state.backup_state();
+        },
+
+        Message::ToggleClientDetails(key) => {
+            state.expanded_client = match state.expanded_client.take() {
+                Some(expanded) if expanded == key => None,
+                _ => Some(key),
+            };
+        },
+
+        Message::ToggleSettings => {
+            state.show_settings = !state.show_settings;
+            state.show_history = false;
+        },
+
+        Message::RuleInputUpdate(AccessList::Allow, input) => state.allowlist_buffer = input,
+        Message::RuleInputUpdate(AccessList::Deny, input) => state.denylist_buffer = input,
+        Message::RuleInputUpdate(AccessList::Proxy, input) => state.proxy_buffer = input,
+
+        Message::AddRule(list) => {
+            let buffer = match list {
+                AccessList::Allow => &mut state.allowlist_buffer,
+                AccessList::Deny => &mut state.denylist_buffer,
+                AccessList::Proxy => &mut state.proxy_buffer,
+            };
+            if let Some(net) = parse_rule(buffer) {
+                buffer.clear();
+                state.access_manager.add_rule(list, net);
+                state.backup_state();
+            }
+        },
+
+        Message::RemoveRule(list, net) => {
+            state.access_manager.remove_rule(list, net);
+            state.backup_state();
+        },
+
+        Message::LimitChanged(kind, input) => {
+            let value = match input.parse::<usize>() {
+                Ok(value) => value,
+                Err(_) if input.is_empty() => 0,
+                Err(_) => return Task::none(),
+            };
+            state.rate_limiter.set_limit(kind, value);
+            state.backup_state();
+        },
+
+        Message::AccessLogEnabled(enabled) => {
+            let config = state.access_log.config();
+            state.access_log.set_config(AccessLogConfig { enabled, ..config });
+            state.backup_state();
+        },
+
+        Message::AccessLogRotation(rotation) => {
+            let config = state.access_log.config();
+            state.access_log.set_config(AccessLogConfig { rotation, ..config });
+            state.backup_state();
+        },
+
+        Message::AccessLogMaxSize(input) => {
+            let max_size_mb = match input.parse::<usize>() {
+                Ok(value) => value,
+                Err(_) if input.is_empty() => 0,
+                Err(_) => return Task::none(),
+            };
+            let config = state.access_log.config();
+            state.access_log.set_config(AccessLogConfig { max_size_mb, ..config });
+            state.backup_state();
+        },
+
+        Message::MetricsEnabled(enabled) => {
+            let config = state.metrics.config();
+            return update_metrics(state, MetricsConfig { enabled, ..config });
+        },
+
+        Message::MetricsLocalOnly(local_only) => {
+            let config = state.metrics.config();
+            return update_metrics(state, MetricsConfig { local_only, ..config });
+        },
+
+        Message::MetricsPortUpdate(port) => {
+            state.metrics_port_buffer = port;
+        },
+
+        Message::ChangeMetricsPort => {
+            let port = match state.metrics_port_buffer.trim() {
+                "" => None,
+                port => match port.parse::<u16>() {
+                    Ok(port) => Some(port),
+                    Err(_) => return Task::none(),
+                },
+            };
+            let config = state.metrics.config();
+            return update_metrics(state, MetricsConfig { port, ..config });
+        },
+
+        Message::BasePathUpdate(base_path) => {
+            state.base_path_buffer = base_path;
+        },
+
+        Message::ChangeBasePath => {
+            let base_path = normalize_base_path(&state.base_path_buffer);
+            state.base_path_buffer = base_path.clone();
+            if base_path == state.base_path {
+                return Task::none();
+            }
+            state.base_path = base_path;
+            state.backup_state();
+            state.qr_code = State::create_qr_code(&state.create_url_string());
+            if state.server_handle.is_some() {
+                return restart_server(state);
+            }
         },
 
         Message::OpenInBrowser => webbrowser::open(&state.create_url_string()).unwrap(),
@@ -156,6 +541,27 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
             let mut ctx = ClipboardContext::new().unwrap();
             ctx.set_contents(state.create_url_string()).unwrap();
         }
+
+        Message::CopyLocalNameUrl => {
+            if let Some(url) = state.create_local_name_url_string() {
+                let mut ctx = ClipboardContext::new().unwrap();
+                ctx.set_contents(url).unwrap();
+            }
+        }
+
+        Message::OpenUrl(url) => {
+            let _ = webbrowser::open(&url);
+        }
+
+        Message::MdnsMessage(MdnsMessage::Found(instance)) => {
+            state.nearby_shares.retain(|share| share.fullname != instance.fullname);
+            state.nearby_shares.push(instance);
+            state.nearby_shares.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        Message::MdnsMessage(MdnsMessage::Removed { fullname }) => {
+            state.nearby_shares.retain(|share| share.fullname != fullname);
+        }
         
         Message::PortTextUpdate(port) => {
             match port.parse::<u16>() {
@@ -169,6 +575,7 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
                 state.local_host = false;
                 state.qr_code = State::create_qr_code(&state.create_url_string());
                 state.block_external_connections.store(false, std::sync::atomic::Ordering::Relaxed);
+                state.backup_state();
             } else {
                 state.ip_adress_public = public_ip_address::perform_lookup(None).map(|lookup|lookup.ip).ok();
             }
@@ -177,10 +584,12 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
         Message::Localhost => {
             state.local_host = true;
             state.qr_code = State::create_qr_code(&state.create_url_string());
+            state.backup_state();
         },
         
         Message::DeleteFile(indx) => {
             state.file_manager.remove(indx);
+            state.backup_state();
             if state.file_manager.get_view().is_empty() {
                 stop_server(state);
             } 
@@ -188,6 +597,7 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
 
         Message::DeleteAllFiles => {
             state.file_manager.clear();
+            state.backup_state();
             stop_server(state);
         },
 
@@ -236,17 +646,14 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
                 return Task::none();
             }
             let port = port.unwrap();
-            if port == state.port {
+            if port == state.port && !matches!(state.server_status, ServerStatus::BindFailed { .. }) {
                 return Task::none();
             }
             state.port = port;
             state.backup_state();
             state.qr_code = State::create_qr_code(&state.create_url_string());
-            if let Some(handle) = &state.server_handle {
-                handle.abort();
-                state.server_handle = None;
-                sleep(std::time::Duration::from_millis(100));
-                return start_server(state);
+            if state.server_handle.is_some() {
+                return restart_server(state);
             }
         },
 
@@ -262,57 +669,239 @@ pub fn update(state: &mut State, message: Message) -> Task<Message> {
             } 
         },
         
-        Message::WindowEvent(Event::Resized(Size { width, height })) => state.size = (width as f32, height as f32),
+        Message::WindowEvent(Event::Resized(Size { width, height })) => state.size = (width, height),
 
         Message::WindowEvent(Event::FileDropped(path)) => {
             add_files_from_path(state, path, false);
+            state.backup_state();
             if state.server_handle.is_none() {
                 return start_server(state);
             }
         },
 
-        Message::ServerMessage(ServerMessage::DownloadAllRequest { ip }) => {
+        Message::ServerMessage(ServerMessage::DownloadAllRequest { client }) => {
+            let invite = client.invite.as_ref().and_then(|token| state.invite_manager.get(token));
             let file_path = state.file_manager.get_view();
-            let file_size = file_path.iter().map(|(_, file)| file.size).sum();
+            let file_size = file_path.iter()
+                .filter(|(_, file)| invite.as_ref().is_none_or(|invite| invite.can_access(&file.path)))
+                .map(|(_, file)| file.size)
+                .sum();
 
-            state.client_manager.add_download(ip, file_size);
+            state.client_manager.add_download(&client, file_size);
         },
 
-        Message::ServerMessage(ServerMessage::Downloaded { index, ip }) => {
+        Message::ServerMessage(ServerMessage::Downloaded { index, client, bytes }) => {
+            apply_progress(state);
             state.file_manager.increment_download_count(index);
-            state.client_manager.download_done(ip);
+            let first_download = state.client_manager.download_done(&client, index);
+            state.client_manager.finish_transfer(&client, index, TransferState::Done);
+            audit(state, AuditEvent::Downloaded, &client, Some(index), bytes);
+            if let Some(token) = &client.invite {
+                state.invite_manager.add_download(token, bytes);
+                state.backup_state();
+            }
+
+            let label = client_label(state, &client);
+            if let Some(file) = state.file_manager.get(index) {
+                let file = file.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                notify(state, NotificationEvent::DownloadCompleted, state.language.notification_download_completed_body(&label, file));
+            }
+            if first_download {
+                let invite = client.invite.as_ref().and_then(|token| state.invite_manager.get(token));
+                let files = state.file_manager.get_view()
+                    .iter()
+                    .filter(|(_, file)| invite.as_ref().is_none_or(|invite| invite.can_access(&file.path)))
+                    .map(|(index, _)| *index);
+                if state.client_manager.downloaded_all(&client, files) {
+                    notify(state, NotificationEvent::AllFilesDownloaded, state.language.notification_all_files_downloaded_body(&label));
+                }
+            }
+
+            let context = HookContext {
+                file: state.file_manager.get(index).map(|file| file.path),
+                ip: Some(client.ip),
+                client: Some(label),
+                bytes: Some(bytes),
+            };
+            return run_hooks(state, HookEvent::Downloaded, context);
         },
 
-        Message::ServerMessage(ServerMessage::ClientConnected { ip }) => {
-            state.client_manager.add_connection(ip);
+        Message::ServerMessage(ServerMessage::DownloadAborted { index, client, bytes }) => {
+            apply_progress(state);
+            state.client_manager.finish_transfer(&client, index, TransferState::Aborted);
+            audit(state, AuditEvent::Aborted, &client, Some(index), bytes);
         },
 
-        Message::ServerMessage(ServerMessage::DownloadRequest { index, ip } ) => {
-            let size = state.file_manager.get(index).map(|file| file.size).unwrap_or(0);
-            state.client_manager.add_download(ip, size);
+        Message::ServerMessage(ServerMessage::ClientConnected { client, user_agent, name }) => {
+            let name = name.or_else(|| client.invite.as_ref()
+                .and_then(|token| state.invite_manager.get(token))
+                .map(|invite| invite.name));
+            let new_client = state.client_manager.get(&client).is_none();
+            if state.client_manager.add_connection(&client, name, user_agent.as_deref()) {
+                audit(state, AuditEvent::Connected, &client, None, 0);
+                let label = client_label(state, &client);
+                if new_client {
+                    notify(state, NotificationEvent::NewClient, state.language.notification_new_client_body(&label));
+                }
+                let context = HookContext { ip: Some(client.ip), client: Some(label), ..HookContext::default() };
+                let hooks = run_hooks(state, HookEvent::ClientConnected, context);
+                if state.resolve_hostnames {
+                    return Task::batch([resolve_hostname(client.ip), hooks]);
+                }
+                return hooks;
+            }
         },
 
-        Message::ServerMessage(ServerMessage::DownloadActive { ip, num_bytes }) => {
-            state.client_manager.download_progress(ip, num_bytes);
+        Message::ResolveHostnames(resolve_hostnames) => {
+            state.resolve_hostnames = resolve_hostnames;
+            state.backup_state();
+            if resolve_hostnames {
+                let ips = state.client_manager.sorted_clients()
+                    .into_iter()
+                    .filter(|(_, client)| client.hostname.is_none())
+                    .map(|(_, client)| client.ip)
+                    .collect::<std::collections::HashSet<_>>();
+                return Task::batch(ips.into_iter().map(resolve_hostname));
+            }
         },
 
-        Message::Refresh => {
-            match local_ip() {
-                Ok(ip) if Some(ip) == state.ip_adress => {},
-                Ok(ip) => {
-                    stop_server(state);
-                    state.ip_adress = Some(ip);
+        Message::NotificationToggled(event, enabled) => {
+            state.notifications.set(event, enabled);
+            state.backup_state();
+        },
+
+        Message::HookEventSelected(event) => {
+            state.hook_event = event;
+        },
+
+        Message::HookKindSelected(kind) => {
+            state.hook_kind = kind;
+        },
+
+        Message::HookInputUpdate(input) => {
+            state.hook_buffer = input;
+        },
+
+        Message::AddHook => {
+            let target = state.hook_buffer.trim().to_string();
+            if target.is_empty() {
+                return Task::none();
+            }
+            state.hooks.push(Hook { event: state.hook_event, kind: state.hook_kind, target });
+            state.hook_buffer.clear();
+            state.backup_state();
+        },
+
+        Message::RemoveHook(index) => {
+            if index < state.hooks.len() {
+                state.hooks.remove(index);
+                state.backup_state();
+            }
+        },
+
+        Message::HookFinished(result) => {
+            if state.hook_results.len() == MAX_HOOK_RESULTS {
+                state.hook_results.pop_back();
+            }
+            state.hook_results.push_front(result);
+        },
+
+        Message::HostnameResolved(ip, hostname) => {
+            if let Some(hostname) = hostname {
+                state.client_manager.set_hostname(ip, hostname);
+            }
+        },
+
+        Message::ToggleHistory => {
+            state.show_history = !state.show_history;
+            state.show_settings = false;
+        },
+
+        Message::HistoryFilterUpdate(filter) => {
+            state.history_filter = filter;
+        },
+
+        Message::HistoryEventFilter(event) => {
+            state.history_event = event;
+        },
+
+        Message::ExportHistory(format) => {
+            let (name, extension) = match format {
+                ExportFormat::Csv => ("CSV", "csv"),
+                ExportFormat::Json => ("JSON", "json"),
+            };
+            let path = FileDialog::new()
+                .add_filter(name, &[extension])
+                .set_file_name(format!("fileshare-history.{}", extension))
+                .save_file();
+            if let Some(path) = path {
+                state.history_error = state.audit_log.export(state.history_event, &state.history_filter, format, &path)
+                    .err()
+                    .map(|error| state.language.history_export_failed(error));
+            }
+        },
+
+        Message::ServerMessage(ServerMessage::DownloadRequest { index, client } ) => {
+            let size = state.file_manager.get(index).map(|file| file.size).unwrap_or(0);
+            state.client_manager.add_download(&client, size);
+        },
+
+        Message::UpdateProgress => apply_progress(state),
+
+        // Late status messages from a server that was already replaced must not overwrite the current one.
+        Message::ServerMessage(
+            ServerMessage::Started { generation, .. }
+            | ServerMessage::BindFailed { generation, .. }
+            | ServerMessage::MetricsBindFailed { generation, .. },
+        ) if generation != state.server_generation => {},
+
+        Message::ServerMessage(ServerMessage::Started { addresses, .. }) => {
+            if let Some(port) = addresses.first().map(|addr| addr.port()) {
+                if port != state.port {
+                    state.port = port;
+                    state.port_buffer = port.to_string();
+                    state.bound_addresses = state.network_manager.bind_addresses(port);
                     state.qr_code = State::create_qr_code(&state.create_url_string());
-                    sleep(std::time::Duration::from_millis(200));
-                    return start_server(state);
-                },
-                Err(_) => {
-                    state.ip_adress = None;
-                    stop_server(state);
                 }
+                let advertised = state.network_manager.display_addresses()
+                    .into_iter()
+                    .map(|interface| interface.ip)
+                    .collect::<Vec<_>>();
+                state.mdns.advertise(&advertised, port, &state.base_path);
             }
+            state.server_status = ServerStatus::Running(addresses);
+        },
 
-            state.client_manager.update();
+        Message::ServerMessage(ServerMessage::BindFailed { port, error, .. }) => {
+            state.server_status = ServerStatus::BindFailed { port, error };
+        },
+
+        Message::ServerMessage(ServerMessage::MetricsBindFailed { port, error, .. }) => {
+            state.metrics_error = Some(state.language.metrics_bind_failed(port, error));
+        },
+
+        Message::PortFallback(port_fallback) => {
+            state.port_fallback = port_fallback;
+            state.backup_state();
+        },
+
+        Message::RunBenchmark => {
+            if state.benchmark_running {
+                return Task::none();
+            }
+            state.benchmark_running = true;
+            return Task::perform(run_benchmark(), Message::BenchmarkDone);
+        },
+
+        Message::BenchmarkDone(result) => {
+            state.benchmark_running = false;
+            state.benchmark = Some(result);
+        },
+
+        Message::Refresh => {
+            state.client_manager.update(&state.connections);
+            state.network_manager.refresh();
+            return update_network(state);
         },
 
         Message::WindowEvent(_) => {}
@@ -326,27 +915,24 @@ fn add_files_from_path_list(state: &mut State, paths: Vec<PathBuf>) {
     for path in paths {
         add_files_from_path(state, path, false) 
     }
+    state.backup_state();
 }
 
 fn add_files_from_path(state: &mut State, path: PathBuf, is_zip: bool) {
-    for result in WalkBuilder::new(path)
+    for entry in WalkBuilder::new(path)
         .hidden(state.ignore_hidden)
         .git_ignore(state.use_gitignore)
         .git_exclude(state.use_gitignore)
         .git_global(state.use_gitignore)
         .ignore(state.use_gitignore)
-        .build() 
+        .build()
+        .flatten()
     {
-        match result {
-            Ok(entry) => {
-                let path = entry.into_path();
-                if path.is_dir() {
-                    continue;
-                } 
-                state.file_manager.push(path, is_zip);
-            },
-            Err(_) => {}
-        }
+        let path = entry.into_path();
+        if path.is_dir() {
+            continue;
+        } 
+        state.file_manager.push(path, is_zip);
     }
     // let paths = find_files(&path);
 
@@ -355,38 +941,345 @@ fn add_files_from_path(state: &mut State, path: PathBuf, is_zip: bool) {
     // } 
 }
 
-fn start_server(state: &mut State) -> Task<Message> {
-    if state.file_manager.get_view().is_empty() {
-        return Task::none();
+fn control(state: &mut State, command: ControlCommand) -> (Value, Task<Message>) {
+    let downloads_active = state.client_manager.active_downloads() > 0;
+    match command {
+        ControlCommand::Open { paths } => (ok(json!({})), update(state, Message::OpenPaths(paths))),
+        ControlCommand::Add { paths } => {
+            if let Some(path) = paths.iter().find(|path| !path.exists()) {
+                return (error(format!("{} does not exist", path.display())), Task::none());
+            }
+            let before = state.file_manager.get_view().len();
+            add_files_from_path_list(state, paths);
+            let added = state.file_manager.get_view().len() - before;
+            let task = match state.server_handle {
+                None => start_server(state),
+                Some(_) => Task::none(),
+            };
+            (ok(json!({ "added": added })), task)
+        },
+        ControlCommand::List => {
+            let files = state.file_manager.get_view().iter()
+                .map(|(index, file)| json!({
+                    "index": index,
+                    "name": file.path.file_name().unwrap_or_default().to_string_lossy(),
+                    "path": file.path,
+                    "size": file.size,
+                    "downloads": file.download_count,
+                    "zip": file.is_zip,
+                }))
+                .collect::<Vec<_>>();
+            let compressing = state.file_manager.get_zip_compressing().into_iter()
+                .map(|(path, zip)| json!({ "folder": path, "files": zip.num_files, "progress": zip.progress }))
+                .collect::<Vec<_>>();
+            let missing = state.file_manager.get_missing().iter()
+                .map(|entry| entry.path())
+                .collect::<Vec<_>>();
+            (ok(json!({ "files": files, "compressing": compressing, "missing": missing })), Task::none())
+        },
+        ControlCommand::Remove { .. } | ControlCommand::Clear if downloads_active => (error("Files can't be removed while downloads are running"), Task::none()),
+        ControlCommand::Remove { index } => match state.file_manager.get(index) {
+            Some(_) => (ok(json!({})), update(state, Message::DeleteFile(index))),
+            None => (error(format!("No shared file with index {}", index)), Task::none()),
+        },
+        ControlCommand::Clear => (ok(json!({})), update(state, Message::DeleteAllFiles)),
+        ControlCommand::Start if state.file_manager.get_view().is_empty() => (error("No files are shared"), Task::none()),
+        ControlCommand::Start => {
+            let task = match state.server_handle {
+                None => start_server(state),
+                Some(_) => Task::none(),
+            };
+            (ok(json!({})), task)
+        },
+        ControlCommand::Stop => {
+            stop_server(state);
+            (ok(json!({})), Task::none())
+        },
+        ControlCommand::Port { port } => {
+            state.port_buffer = port.to_string();
+            let task = update(state, Message::ChangePort);
+            (ok(json!({ "port": state.port })), task)
+        },
+        ControlCommand::Address { public: true } => {
+            let mut task = update(state, Message::PublicIp);
+            // The first attempt only looks up the public address if it wasn't known yet.
+            if state.local_host && state.ip_adress_public.is_some() {
+                task = update(state, Message::PublicIp);
+            }
+            match state.local_host {
+                true => (error("The public IP address could not be determined"), task),
+                false => (ok(json!({ "url": state.create_url_string() })), task),
+            }
+        },
+        ControlCommand::Address { public: false } => {
+            let task = update(state, Message::Localhost);
+            (ok(json!({ "url": state.create_url_string() })), task)
+        },
+        ControlCommand::Url { qr } => {
+            let url = state.create_url_string();
+            (ok(json!({
+                "url": url,
+                "local_name_url": state.create_local_name_url_string(),
+                "qr": if qr { qr_text(&url) } else { None },
+            })), Task::none())
+        },
+        ControlCommand::Status => {
+            let (server, addresses, failure) = match &state.server_status {
+                ServerStatus::Stopped => ("stopped", Vec::new(), None),
+                ServerStatus::Starting => ("starting", Vec::new(), None),
+                ServerStatus::Running(addresses) => ("running", addresses.clone(), None),
+                ServerStatus::BindFailed { error, .. } => ("bind_failed", Vec::new(), Some(error.clone())),
+            };
+            (ok(json!({
+                "server": server,
+                "addresses": addresses,
+                "error": failure,
+                "port": state.port,
+                "url": state.create_url_string(),
+                "files": state.file_manager.get_view().len(),
+                "clients": state.client_manager.num_clients(),
+                "active_downloads": state.client_manager.active_downloads(),
+            })), Task::none())
+        },
     }
-    if state.ip_adress.is_none() || state.ip_adress != local_ip().ok() {
-        return Task::none();
+}
+
+fn current_profile(state: &State, name: String) -> Profile {
+    Profile {
+        name,
+        port: state.port,
+        theme: state.theme.get_indx(),
+        interfaces: state.network_manager.selection().clone(),
+        access_rules: state.access_manager.rules(),
+        limits: state.rate_limiter.limits(),
+        block_external_connections: state.block_external_connections.load(std::sync::atomic::Ordering::Relaxed),
+        ask_new_clients: state.access_manager.ask_new_clients(),
+        shared: state.file_manager.shared_entries(),
+    }
+}
+
+fn apply_profile(state: &mut State, profile: Profile) -> Task<Message> {
+    stop_server(state);
+    let compressing = state.file_manager.get_zip_compressing()
+        .into_iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    compressing.iter().for_each(|path| state.file_manager.zip_compressing_canceld(path));
+    state.file_manager.clear();
+
+    state.theme.set_indx(profile.theme);
+    state.port = profile.port;
+    state.port_buffer = profile.port.to_string();
+    state.network_manager.set_selection(profile.interfaces);
+    state.access_manager.set_rules(profile.access_rules);
+    state.rate_limiter.set_limits(profile.limits);
+    state.block_external_connections.store(profile.block_external_connections, std::sync::atomic::Ordering::Relaxed);
+    state.access_manager.set_ask_new_clients(profile.ask_new_clients);
+    state.active_profile = Some(profile.name);
+
+    let displayed = state.network_manager.display_addresses();
+    if !displayed.iter().any(|interface| Some(interface.ip) == state.ip_adress) {
+        state.ip_adress = displayed.first().map(|interface| interface.ip);
     }
+    state.bound_addresses = state.network_manager.bind_addresses(state.port);
+    state.qr_code = State::create_qr_code(&state.create_url_string());
+    state.backup_state();
+
+    restore_shared_files(state, profile.shared)
+}
 
-    let filepaths = state.file_manager.get_arc();
-    let block_external_connections = state.block_external_connections.clone();
-    let ip_adress = state.ip_adress;
-    let port = state.port;
-    let current_theme = state.theme.get_arc().clone();
-    let stream = channel(10, move |tx: futures::channel::mpsc::Sender<_>| {
+fn start_zip(state: &mut State, path: PathBuf, use_gitignore: bool, ignore_hidden: bool) -> Task<Message> {
+    let path_clone = path.clone();
+    let stream = channel(100, move |tx: futures::channel::mpsc::Sender<_>| {
         let tx = tx.clone();
+        let path = path.clone();
         async move {
-            server(ip_adress.unwrap(), port, filepaths, tx, block_external_connections, current_theme.clone()).await;
+            FileManager::start_zip_task(path, tx, use_gitignore, ignore_hidden).await;
         }
     });
 
+    let task = Task::run(stream, |server_message| {
+        Message::ZipMessage(server_message)
+    });
+
+    let (task, handle) = Task::abortable(task);
+    state.file_manager.add_new_zip_compressing(path_clone, handle, use_gitignore, ignore_hidden);
+    task
+}
+
+pub fn restore_shared_files(state: &mut State, entries: Vec<SharedEntry>) -> Task<Message> {
+    if entries.is_empty() {
+        return Task::none();
+    }
+    let mut tasks = Vec::new();
+    for entry in entries {
+        if !entry.exists() {
+            state.file_manager.add_missing(entry);
+            continue;
+        }
+        match entry {
+            SharedEntry::File(path) => state.file_manager.push(path, false),
+            SharedEntry::Zip { folder, use_gitignore, ignore_hidden } => {
+                if !state.file_manager.already_compressed(&folder) {
+                    tasks.push(start_zip(state, folder, use_gitignore, ignore_hidden));
+                }
+            },
+        }
+    }
+    state.backup_state();
+    if state.server_handle.is_none() {
+        tasks.push(start_server(state));
+    }
+    Task::batch(tasks)
+}
+
+fn audit(state: &mut State, event: AuditEvent, client: &ClientId, index: Option<usize>, bytes: usize) {
+    let name = state.client_manager.get(client)
+        .and_then(|info| info.name.clone())
+        .or_else(|| client.invite.as_ref()
+            .and_then(|token| state.invite_manager.get(token))
+            .map(|invite| invite.name));
+    let file = index
+        .and_then(|index| state.file_manager.get(index))
+        .and_then(|file| file.path.file_name().map(|name| name.to_string_lossy().to_string()));
+    state.history_error = state.audit_log.append(AuditEntry::new(event, client.ip, name, file, bytes))
+        .err()
+        .map(|error| state.language.history_write_failed(error));
+}
+
+fn run_hooks(state: &State, event: HookEvent, context: HookContext) -> Task<Message> {
+    Task::batch(state.hooks.iter()
+        .filter(|hook| hook.event == event)
+        .map(|hook| Task::perform(run_hook(hook.clone(), context.clone()), Message::HookFinished)))
+}
+
+fn client_label(state: &State, client: &ClientId) -> String {
+    state.client_manager.get(client)
+        .and_then(|info| info.label().cloned())
+        .unwrap_or_else(|| client.ip.to_string())
+}
+
+fn notify(state: &State, event: NotificationEvent, body: String) {
+    if !state.notifications.get(event) {
+        return;
+    }
+    let summary = match event {
+        NotificationEvent::NewClient => state.language.notification_new_client(),
+        NotificationEvent::DownloadCompleted => state.language.notification_download_completed(),
+        NotificationEvent::AllFilesDownloaded => state.language.notification_all_files_downloaded(),
+        NotificationEvent::ZipFinished => state.language.notification_zip_finished(),
+    };
+    show_notification(summary.to_string(), body);
+}
+
+fn resolve_hostname(ip: IpAddr) -> Task<Message> {
+    if ip.is_loopback() {
+        return Task::none();
+    }
+    Task::perform(
+        tokio::task::spawn_blocking(move || dns_lookup::lookup_addr(&ip).ok().filter(|hostname| hostname.parse::<IpAddr>().is_err())),
+        move |hostname| Message::HostnameResolved(ip, hostname.ok().flatten()),
+    )
+}
+
+fn apply_progress(state: &mut State) {
+    for (client, index, num_bytes) in state.progress.drain() {
+        if let Some(file) = state.file_manager.get(index) {
+            let name = file.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            state.client_manager.start_transfer(&client, index, name, file.size);
+        }
+        state.client_manager.download_progress(&client, index, num_bytes);
+    }
+    state.metrics.update(&state.client_manager, &state.file_manager);
+}
+
+fn update_metrics(state: &mut State, config: MetricsConfig) -> Task<Message> {
+    let previous = state.metrics.config();
+    state.metrics.set_config(config);
+    state.metrics_error = None;
+    state.backup_state();
+    let separate_server = |config: MetricsConfig| config.enabled && config.port.is_some();
+    if (separate_server(previous) || separate_server(config)) && state.server_handle.is_some() {
+        return restart_server(state);
+    }
+    Task::none()
+}
+
+fn update_network(state: &mut State) -> Task<Message> {
+    let displayed = state.network_manager.display_addresses();
+    if !displayed.iter().any(|interface| Some(interface.ip) == state.ip_adress) {
+        state.ip_adress = displayed.first().map(|interface| interface.ip);
+        state.qr_code = State::create_qr_code(&state.create_url_string());
+    }
+
+    let addresses = state.network_manager.bind_addresses(state.port);
+    if addresses == state.bound_addresses {
+        return Task::none();
+    }
+    state.bound_addresses = addresses;
+    restart_server(state)
+}
+
+fn start_server(state: &mut State) -> Task<Message> {
+    if state.file_manager.get_view().is_empty() {
+        return Task::none();
+    }
+    let addresses = state.network_manager.bind_addresses(state.port);
+    if addresses.is_empty() {
+        return Task::none();
+    }
+    state.bound_addresses = addresses.clone();
+
+    let server_state = ServerState {
+        files: state.file_manager.get_arc(),
+        invites: state.invite_manager.clone(),
+        block_external_connections: state.block_external_connections.clone(),
+        access: state.access_manager.clone(),
+        limiter: state.rate_limiter.clone(),
+        theme: state.theme.get_arc(),
+        base_path: state.base_path.clone(),
+        local_addresses: state.network_manager.local_addresses(),
+        connections: state.connections.clone(),
+        progress: state.progress.clone(),
+        access_log: state.access_log.clone(),
+        metrics: state.metrics.clone(),
+    };
+    let shutdown = CancellationToken::new();
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    state.server_generation += 1;
+    let server = server(addresses, state.port_fallback, server_state, state.server_generation, shutdown.clone(), tx);
+    let server = futures::stream::once(server).filter_map(|_| async { None });
+    let stream = futures::stream::select(rx, server);
+
     let task = Task::run(stream, |server_message| {
         Message::ServerMessage(server_message)
     });
 
     let (task, handle) = Task::abortable(task);
     state.server_handle = Some(handle);
+    state.server_shutdown = Some(shutdown);
+    state.server_status = ServerStatus::Starting;
     task
 }
 
+/// Stops sharing, downloads that are still running are cut off as well.
 fn stop_server(state: &mut State) {
-    if let Some(handle) = &state.server_handle {
-        handle.abort();
-        state.server_handle = None;
+    state.access_manager.disconnect_all();
+    stop_listening(state);
+}
+
+/// Replaces the listener, running downloads finish on the old one.
+fn restart_server(state: &mut State) -> Task<Message> {
+    stop_listening(state);
+    start_server(state)
+}
+
+fn stop_listening(state: &mut State) {
+    if let Some(shutdown) = state.server_shutdown.take() {
+        shutdown.cancel();
     }
+    state.server_generation += 1;
+    state.server_handle = None;
+    state.server_status = ServerStatus::Stopped;
+    state.mdns.stop();
 }
\ No newline at end of file
@@ -0,0 +1,46 @@
+use iced::widget::{button, center, column, container, horizontal_rule, opaque, row, text};
+use crate::{state::state::State, state::update::Message, views::styles::CustomStyles};
+
+use super::root_view::{DOWNLOAD_PANE_WIDTH, H2_SIZE, P_SIZE};
+
+pub fn approval_pane(state: &State) -> Option<iced::Element<'_, Message>> {
+    let pending = state.access_manager.pending();
+    if pending.is_empty() {
+        return None;
+    }
+
+    let mut requests = column![
+        text(state.language.new_client_title()).size(H2_SIZE),
+        horizontal_rule(5).style(CustomStyles::horizontal_rule),
+    ]
+    .spacing(10);
+
+    for ip in pending {
+        let ip_text = text(state.language.new_client(ip))
+            .size(P_SIZE);
+
+        let buttons = row![
+            button(state.language.allow())
+                .on_press(Message::ApproveClient(ip))
+                .width(iced::Length::FillPortion(1)),
+            button(state.language.deny())
+                .on_press(Message::DenyClient(ip))
+                .style(button::danger)
+                .width(iced::Length::FillPortion(1)),
+        ]
+        .spacing(5);
+
+        requests = requests.push(column![ip_text, buttons].spacing(5));
+    }
+
+    let requests = container(requests)
+        .padding(15)
+        .width(iced::Length::Fixed(DOWNLOAD_PANE_WIDTH * 1.5))
+        .style(CustomStyles::darker_background(0.8));
+
+    let requests = container(requests)
+        .padding(1.0)
+        .style(CustomStyles::container_border(true));
+
+    Some(opaque(center(opaque(requests))))
+}
@@ -1,11 +1,11 @@
-use std::time::Duration;
+use std::{sync::atomic::Ordering, time::Duration};
 
-use iced::widget::{column, container, horizontal_rule, row, text, tooltip, Space};
-use crate::{server::webpage_service::size_string, state::{client_manager::ClientState, state::State}, state::update::Message, views::styles::CustomStyles};
+use iced::widget::{button, canvas, column, container, horizontal_rule, hover, row, text, tooltip, Space};
+use crate::{server::webpage_service::size_string, state::{client_manager::{ClientInfo, ClientState, TransferState}, state::State}, state::update::Message, views::styles::CustomStyles};
 
-use super::root_view::{CONNECTION_PANE_WIDTH, H1_SIZE, P_SIZE};
+use super::{root_view::{CONNECTION_PANE_WIDTH, H1_SIZE, P_SIZE}, throughput_chart::ThroughputChart};
 
-pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
+pub fn connection_info_pane(state: &State) -> iced::Element<'_, Message> {
     let text_connections = text(state.language.connections())
         .size(H1_SIZE)
         .align_x(iced::alignment::Horizontal::Center)
@@ -15,7 +15,7 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
 
     let clients = state.client_manager.sorted_clients();
 
-    for (indx, (ip, client_info)) in clients.iter().enumerate() {
+    for (indx, (key, client_info)) in clients.iter().enumerate() {
 
         let color = match client_info.state {
             ClientState::Downloading => state.theme.get().palette().primary,
@@ -23,10 +23,19 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
             ClientState::Disconnected => state.theme.get().palette().danger,
         };
 
-        let text_ip = text!("{}", ip.to_string())
-            .size(P_SIZE)
-            .width(iced::Length::Fill)
-            .color(color);
+        let label = client_info.label();
+        let details = match &client_info.device {
+            Some(device) if label != Some(device) => format!("{} · {}", client_info.ip, device),
+            _ => client_info.ip.to_string(),
+        };
+        let text_ip = match label {
+            Some(label) => column![
+                text(label).size(P_SIZE).color(color),
+                text(details).size(10),
+            ],
+            None => column![text(details).size(P_SIZE).color(color)],
+        }
+        .width(iced::Length::Fill);
 
         let text_count = column![
             text!("{} Downloads", client_info.download_count).size(12),
@@ -52,6 +61,13 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
             Space::new(iced::Length::Shrink, iced::Length::Fixed(12.0)),
             conection
         ];
+
+        if client_info.history.iter().any(|sample| *sample > 0) {
+            let sparkline = canvas(ThroughputChart::new(&client_info.history, color))
+                .width(iced::Length::Fill)
+                .height(iced::Length::Fixed(16.0));
+            conection = conection.push(sparkline);
+        }
         
         if client_info.state == ClientState::Downloading {
             conection = conection.push(progress_bar);
@@ -70,6 +86,20 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
             .padding(2)
             .style(CustomStyles::darker_background(factor));
 
+        let ip = client_info.ip;
+        let actions = row![
+            button(text(state.language.details()).size(10)).on_press(Message::ToggleClientDetails((*key).clone())).padding(3),
+            button(text(state.language.disconnect()).size(10)).on_press(Message::DisconnectClient(ip)).padding(3),
+            button(text(state.language.block()).size(10)).on_press(Message::BlockClient(ip)).style(button::danger).padding(3),
+            button(text(state.language.trust()).size(10)).on_press(Message::TrustClient(ip)).padding(3),
+        ]
+        .spacing(3);
+
+        let conection = hover(conection, container(actions)
+            .padding(3)
+            .height(iced::Length::Fill)
+            .align_y(iced::alignment::Vertical::Bottom));
+
         let last_connection_text = match client_info.state {
             ClientState::Downloading => state.language.downloading_tooltip(size_string(client_info.max_speed), size_string(client_info.current_download_progress), size_string(client_info.current_downloads_size)),
             ClientState::Connected => {
@@ -103,6 +133,10 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
         );
 
         connections = connections.push(conection);
+
+        if state.expanded_client.as_ref() == Some(*key) {
+            connections = connections.push(transfer_list(state, client_info));
+        }
     }
 
     let connections: iced::Element<Message> = iced::widget::scrollable(connections).style(CustomStyles::scrollable)
@@ -118,6 +152,22 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
         .width(iced::Length::Fill)
         .align_x(iced::alignment::Horizontal::Center);
 
+    let rejected = state.rate_limiter.rejected();
+    let rejected_tooltip = text(state.language.rejected_requests_tooltip(
+        rejected.connections_per_ip.load(Ordering::Relaxed),
+        rejected.clients.load(Ordering::Relaxed),
+        rejected.requests_per_second.load(Ordering::Relaxed),
+    ))
+    .size(P_SIZE);
+
+    let rejected_text = tooltip(
+        text(state.language.rejected_requests()).size(P_SIZE).width(iced::Length::Shrink),
+        container(rejected_tooltip)
+            .padding(10)
+            .style(container::rounded_box),
+        tooltip::Position::Top
+    );
+
     let name_column = column![
         text(state.language.active_downlaods()).size(P_SIZE).width(iced::Length::Shrink),
         text(state.language.active_clients()).size(P_SIZE).width(iced::Length::Shrink),
@@ -125,6 +175,7 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
         text(state.language.total_downloads()).size(P_SIZE).width(iced::Length::Shrink),
         text(state.language.current_upload()).size(P_SIZE).width(iced::Length::Shrink),
         text(state.language.transmitted_data()).size(P_SIZE).width(iced::Length::Shrink),
+        rejected_text,
     ]
     .spacing(5);
 
@@ -135,6 +186,7 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
         text!("{}", state.client_manager.total_downloads()).size(P_SIZE).align_x(iced::alignment::Horizontal::Right).width(iced::Length::Fill),
         text!("{}/s", size_string(state.client_manager.throughput())).size(P_SIZE).align_x(iced::alignment::Horizontal::Right).width(iced::Length::Fill),
         text!("{}", size_string(state.client_manager.transmitted_data())).size(P_SIZE).align_x(iced::alignment::Horizontal::Right).width(iced::Length::Fill),
+        text!("{}", rejected.total()).size(P_SIZE).align_x(iced::alignment::Horizontal::Right).width(iced::Length::Fill),
     ]
     .spacing(5);
 
@@ -142,12 +194,21 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
         .spacing(5)
         .width(iced::Length::Fill);
 
+    let chart = canvas(ThroughputChart::new(state.client_manager.history(), state.theme.get().palette().primary).label(true))
+        .width(iced::Length::Fill)
+        .height(iced::Length::Fixed(60.0));
+
+    let chart = container(chart)
+        .padding(1)
+        .style(CustomStyles::container_border(false));
+
     let connections = column![
         text_connections, 
         horizontal_rule(5).style(CustomStyles::horizontal_rule),
         connections, 
         stats_text,
         horizontal_rule(5).style(CustomStyles::horizontal_rule),
+        chart,
         stats_row
     ]
     .padding(5)
@@ -162,6 +223,52 @@ pub fn connection_info_pane(state: &State) -> iced::Element<Message> {
     connections.into()
 }
 
+fn transfer_list<'a>(state: &'a State, client_info: &'a ClientInfo) -> iced::Element<'a, Message> {
+    let mut transfers = column![].spacing(6);
+
+    if client_info.transfers.is_empty() {
+        transfers = transfers.push(text(state.language.no_transfers()).size(12));
+    }
+
+    for transfer in client_info.transfers.iter().rev() {
+        let (status, color) = match transfer.state {
+            TransferState::Active => (format!("{}/s", size_string(transfer.speed)), state.theme.get().palette().primary),
+            TransferState::Done => (state.language.transfer_done().to_string(), state.theme.get().palette().success),
+            TransferState::Aborted => (state.language.transfer_aborted().to_string(), state.theme.get().palette().danger),
+        };
+        let duration = transfer.end.unwrap_or_else(std::time::Instant::now) - transfer.start;
+
+        let header = row![
+            text(&transfer.name).size(12).color(color).width(iced::Length::Fill),
+            text(status).size(10),
+        ]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+
+        let details = text(state.language.transfer_details(
+            size_string(transfer.sent),
+            size_string(transfer.size),
+            format_time(duration),
+            format_time(transfer.start.elapsed()),
+        ))
+        .size(10);
+
+        let progress_bar = iced::widget::progress_bar(
+            0.0..=transfer.size.max(1) as f32,
+            transfer.sent as f32
+        ).height(4.0)
+        .style(CustomStyles::progress_bar);
+
+        transfers = transfers.push(column![header, progress_bar, details].spacing(2));
+    }
+
+    container(transfers)
+        .padding([6, 14])
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6))
+        .into()
+}
+
 pub fn format_time(time: Duration) -> String {
     let secs = time.as_secs();
     let mins = secs / 60;
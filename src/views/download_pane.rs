@@ -1,9 +1,9 @@
-use iced::widget::{self, button, checkbox, column, container, horizontal_rule, row, text, tooltip};
-use crate::{state::state::State, views::styles::CustomStyles, state::update::Message};
+use iced::widget::{self, button, checkbox, column, container, horizontal_rule, pick_list, row, text, tooltip};
+use crate::{server::webpage_service::size_string, state::{invite_manager::InviteExpiry, state::State}, views::styles::CustomStyles, state::update::Message};
 
-use super::root_view::{DOWNLOAD_PANE_WIDTH, H1_SIZE, H2_SIZE, P_SIZE};
+use super::{connection_info_pane::format_time, root_view::{DOWNLOAD_PANE_WIDTH, H1_SIZE, H2_SIZE, P_SIZE}};
 
-pub fn download_pane(state: &State) -> iced::Element<Message> {
+pub fn download_pane(state: &State) -> iced::Element<'_, Message> {
     let image = widget::image(&state.qr_code)
         .width(iced::Length::Fill);
 
@@ -51,6 +51,36 @@ pub fn download_pane(state: &State) -> iced::Element<Message> {
         tooltip::Position::Bottom
     );
 
+    let ask_new_clients = checkbox(state.language.ask_new_clients(), state.access_manager.ask_new_clients())
+        .on_toggle(Message::AskNewClients)
+        .size(16)
+        .text_size(16)
+        .width(iced::Length::Fill);
+
+    let ask_new_clients = tooltip(
+        ask_new_clients,
+        container(text(state.language.ask_new_clients_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let resolve_hostnames = checkbox(state.language.resolve_hostnames(), state.resolve_hostnames)
+        .on_toggle(Message::ResolveHostnames)
+        .size(16)
+        .text_size(16)
+        .width(iced::Length::Fill);
+
+    let resolve_hostnames = tooltip(
+        resolve_hostnames,
+        container(text(state.language.resolve_hostnames_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
     let url_buttons_row = row![
         copy_button,
         browser_button
@@ -64,7 +94,7 @@ pub fn download_pane(state: &State) -> iced::Element<Message> {
     .spacing(5);
 
     let show_qr_code = checkbox(state.language.show_qr_code(), state.show_qr_code)
-        .on_toggle(|show| Message::ShowQrCode(show))
+        .on_toggle(Message::ShowQrCode)
         .size(16)
         .text_size(16)
         .width(iced::Length::Fill);
@@ -75,23 +105,46 @@ pub fn download_pane(state: &State) -> iced::Element<Message> {
         text_mode,
         select_row,
         block_external_connections,
+        ask_new_clients,
+        resolve_hostnames,
         text_connection_info,
-        url_text_field.width(iced::Length::Fill),
-        url_buttons_row,
-        show_qr_code,
     ]
     .padding(5)
     .spacing(10)
     .width(iced::Length::Fill)
-    .height(iced::Length::Fill)
     .align_x(iced::alignment::Horizontal::Center);
 
+    let interfaces = state.network_manager.display_addresses();
+    if state.local_host && interfaces.len() > 1 {
+        let selected = interfaces.iter().find(|interface| Some(interface.ip) == state.ip_adress).cloned();
+        let interface_list = pick_list(interfaces, selected, Message::DisplayInterfaceChanged)
+            .text_size(P_SIZE)
+            .style(CustomStyles::pick_list)
+            .width(iced::Length::Fill);
+        download_pane = download_pane.push(interface_list);
+    }
+
+    download_pane = download_pane
+        .push(url_text_field.width(iced::Length::Fill))
+        .push(url_buttons_row)
+        .push(show_qr_code);
+
+    if let Some(local_name_url) = state.create_local_name_url_string() {
+        download_pane = download_pane.push(local_name_section(state, local_name_url));
+    }
+
     if state.show_qr_code {
         download_pane = download_pane.push(horizontal_rule(5).style(CustomStyles::horizontal_rule));
         download_pane = download_pane.push(image);
-        download_pane = download_pane.push(horizontal_rule(5).style(CustomStyles::horizontal_rule));
     }
 
+    download_pane = download_pane.push(invite_section(state));
+    download_pane = download_pane.push(nearby_shares_section(state));
+
+    let download_pane = iced::widget::scrollable(download_pane)
+        .style(CustomStyles::scrollable)
+        .height(iced::Length::Fill);
+
     let download_pane = container(download_pane)
         .style(CustomStyles::darker_background(0.8))
         .width(iced::Length::Fixed(DOWNLOAD_PANE_WIDTH))
@@ -99,4 +152,185 @@ pub fn download_pane(state: &State) -> iced::Element<Message> {
         .padding(5);
 
     download_pane.into()
-}
\ No newline at end of file
+}
+
+fn invite_section(state: &State) -> iced::Element<'_, Message> {
+    let invites_text = text(state.language.invites())
+        .size(H2_SIZE);
+
+    let invites_text = tooltip(
+        invites_text,
+        container(text(state.language.invite_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(200.0))
+            .style(container::rounded_box),
+        tooltip::Position::Top
+    );
+
+    let name_input = widget::text_input(state.language.invite_name(), &state.invite_name_buffer)
+        .size(P_SIZE)
+        .on_input(Message::InviteNameUpdate)
+        .on_submit(Message::CreateInvite)
+        .width(iced::Length::Fill);
+
+    let expiry_list = pick_list(InviteExpiry::all_variants(), Some(state.invite_expiry), Message::InviteExpiryChanged)
+        .text_size(P_SIZE)
+        .style(CustomStyles::pick_list);
+
+    let create_row = row![name_input, expiry_list]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+
+    let create_button = button(state.language.create_invite())
+        .on_press(Message::CreateInvite)
+        .width(iced::Length::Fill);
+
+    let require_invite = checkbox(state.language.require_invite(), state.invite_manager.require_invite())
+        .on_toggle(Message::RequireInvite)
+        .size(16)
+        .text_size(16)
+        .width(iced::Length::Fill);
+
+    let require_invite = tooltip(
+        require_invite,
+        container(text(state.language.require_invite_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let public_button = button(text(state.language.public_link()).size(P_SIZE))
+        .on_press(Message::SelectInvite(None))
+        .style(if state.selected_invite.is_none() { button::primary } else { button::secondary })
+        .width(iced::Length::Fill);
+
+    let mut invite_list = column![public_button].spacing(5);
+
+    for (indx, invite) in state.invite_manager.get_view().into_iter().enumerate() {
+        let selected = state.selected_invite.as_ref() == Some(&invite.token);
+        let editing = state.editing_invite.as_ref() == Some(&invite.token);
+
+        let name_button = button(text(invite.name.clone()).size(P_SIZE))
+            .on_press(Message::SelectInvite(Some(invite.token.clone())))
+            .style(if selected { button::primary } else { button::secondary })
+            .width(iced::Length::Fill);
+
+        let files_button = if editing {
+            button(text(state.language.done()).size(P_SIZE)).on_press(Message::EditInviteFiles(None))
+        } else {
+            button(text(state.language.invite_files()).size(P_SIZE)).on_press(Message::EditInviteFiles(Some(invite.token.clone())))
+        };
+
+        let delete_button = button(text(" X ").size(P_SIZE))
+            .on_press(Message::DeleteInvite(invite.token.clone()))
+            .style(button::danger);
+
+        let expiry_text = match invite.remaining() {
+            _ if invite.is_expired() => state.language.invite_expired().to_string(),
+            Some(remaining) => state.language.invite_expires(format_time(remaining)),
+            None => String::new(),
+        };
+
+        let files_text = match &invite.files {
+            Some(files) => state.language.num_files(files.len()),
+            None => state.language.all_files().to_string(),
+        };
+
+        let stats_text = state.language.invite_stats(invite.downloads, size_string(invite.downloaded_bytes));
+
+        let info_text = text!("{}  {}  {}", files_text, stats_text, expiry_text)
+            .size(12);
+
+        let invite = column![
+            row![name_button, files_button, delete_button].spacing(5),
+            info_text,
+        ]
+        .spacing(2);
+
+        let factor = if indx & 1 == 0 { 0.9 } else { 0.7 };
+        let invite = container(invite)
+            .padding(5)
+            .style(CustomStyles::darker_background(factor));
+
+        invite_list = invite_list.push(invite);
+    }
+
+    column![
+        horizontal_rule(5).style(CustomStyles::horizontal_rule),
+        invites_text,
+        create_row,
+        create_button,
+        require_invite,
+        invite_list,
+    ]
+    .spacing(10)
+    .width(iced::Length::Fill)
+    .into()
+}
+
+fn local_name_section(state: &State, url: String) -> iced::Element<'_, Message> {
+    let local_name_text = tooltip(
+        text(state.language.local_name()).size(P_SIZE),
+        container(text(state.language.local_name_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let url_text_field = widget::text_input("", &url)
+        .size(P_SIZE)
+        .on_input(|_| Message::None)
+        .width(iced::Length::Fill);
+
+    let copy_button = button(text(state.language.copy_url()).size(P_SIZE))
+        .on_press(Message::CopyLocalNameUrl);
+
+    column![
+        local_name_text,
+        row![url_text_field, copy_button]
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center),
+    ]
+    .spacing(5)
+    .width(iced::Length::Fill)
+    .into()
+}
+
+fn nearby_shares_section(state: &State) -> iced::Element<'_, Message> {
+    let nearby_text = text(state.language.nearby_shares())
+        .size(H2_SIZE);
+
+    let own_fullname = state.mdns.fullname();
+    let shares = state.nearby_shares.iter()
+        .filter(|share| Some(&share.fullname) != own_fullname)
+        .collect::<Vec<_>>();
+
+    let mut share_list = column![].spacing(5);
+
+    if shares.is_empty() {
+        share_list = share_list.push(text(state.language.no_nearby_shares()).size(P_SIZE));
+    }
+
+    for share in shares {
+        let share_button = button(column![
+                text(share.name.clone()).size(P_SIZE),
+                text(share.url.clone()).size(12),
+            ])
+            .on_press(Message::OpenUrl(share.url.clone()))
+            .style(button::secondary)
+            .width(iced::Length::Fill);
+
+        share_list = share_list.push(share_button);
+    }
+
+    column![
+        horizontal_rule(5).style(CustomStyles::horizontal_rule),
+        nearby_text,
+        share_list,
+    ]
+    .spacing(10)
+    .width(iced::Length::Fill)
+    .into()
+}
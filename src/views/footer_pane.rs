@@ -1,9 +1,9 @@
-use iced::widget::{self, container, pick_list, row, text, tooltip};
-use crate::{state::state::State, state::update::Message, views::{styles::CustomStyles, styles::color_multiply}};
+use iced::widget::{self, button, container, pick_list, row, text, tooltip};
+use crate::{state::state::{ServerStatus, State}, state::update::Message, views::{styles::CustomStyles, styles::color_multiply}};
 
 use super::{language::Language, root_view::{H2_SIZE, P_SIZE}};
 
-pub fn footer_pane(state: &State) -> iced::Element<Message> {
+pub fn footer_pane(state: &State) -> iced::Element<'_, Message> {
     let settings_text = text!("Theme:")
         .size(H2_SIZE);
 
@@ -24,14 +24,11 @@ pub fn footer_pane(state: &State) -> iced::Element<Message> {
         .size(H2_SIZE);
 
     let mut port_text = widget::text_input("Port", &state.port_buffer)
-        .width(iced::Length::Fixed(100.0));
-
-    if state.client_manager.active_downloads() == 0 {
-        port_text = port_text.on_submit(Message::ChangePort);
-        port_text = port_text.on_input(Message::PortTextUpdate);
-    }
+        .width(iced::Length::Fixed(100.0))
+        .on_submit(Message::ChangePort)
+        .on_input(Message::PortTextUpdate);
 
-    let mut port_tooltip = match state.port_buffer.parse::<u16>() {
+    let port_tooltip = match state.port_buffer.parse::<u16>() {
         Err(_) => {
             port_text = port_text.style(CustomStyles::textfield_background(state.theme.get().palette().danger));
             state.language.invalid_port(state.port)
@@ -46,10 +43,6 @@ pub fn footer_pane(state: &State) -> iced::Element<Message> {
         }
     };
 
-    if state.client_manager.active_downloads() > 0 {
-        port_tooltip = state.language.locked_port(state.port);
-    }
-
     let port_tooltip = text(port_tooltip)
         .size(P_SIZE);
 
@@ -70,14 +63,53 @@ pub fn footer_pane(state: &State) -> iced::Element<Message> {
     let language_button = pick_list(Language::all_variants(), Some(state.language), Message::LanguageChanged)
         .style(CustomStyles::pick_list);
 
-    let footer = row![
+    let settings_button = button(state.language.settings())
+        .on_press(Message::ToggleSettings);
+
+    let history_button = button(state.language.history())
+        .on_press(Message::ToggleHistory);
+
+    let profile_title = text(state.language.profile())
+        .size(H2_SIZE);
+
+    let profile_names = state.profiles.iter().map(|profile| profile.name.clone()).collect::<Vec<_>>();
+    let profile_button = pick_list(profile_names, state.active_profile.clone(), Message::ProfileSelected)
+        .placeholder("-")
+        .style(CustomStyles::pick_list);
+
+    let palette = state.theme.get().palette();
+    let (status_color, status_text) = match &state.server_status {
+        ServerStatus::Stopped => (color_multiply(palette.text, 0.5), state.language.server_stopped().to_string()),
+        ServerStatus::Starting => (palette.primary, state.language.server_starting().to_string()),
+        ServerStatus::Running(addresses) => (palette.success, addresses.iter().fold(state.language.server_running().to_string(), |text, addr| format!("{}\n{}", text, addr))),
+        ServerStatus::BindFailed { port, error } => (palette.danger, state.language.server_bind_failed(port, error)),
+    };
+
+    let server_status = tooltip(
+        text("●").size(H2_SIZE).color(status_color),
+        container(text(status_text).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(250.0))
+            .style(container::rounded_box),
+        tooltip::Position::Top
+    );
+
+    let mut footer = row![
+        settings_button,
+        history_button,
+    ];
+    if !state.profiles.is_empty() {
+        footer = footer.push(profile_title).push(profile_button);
+    }
+    let footer = footer.push(row![
         text_view,
         language_button,
         settings_text,
         theme_button,
         port_title,
         port_text,
-    ]
+        server_status,
+    ].spacing(20).align_y(iced::alignment::Vertical::Center))
     .spacing(20)
     .padding(10)
     .width(iced::Length::Shrink)
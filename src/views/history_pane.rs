@@ -0,0 +1,112 @@
+use iced::widget::{self, button, column, container, horizontal_rule, row, text};
+use crate::{server::webpage_service::size_string, state::{audit_log::{AuditEvent, ExportFormat}, state::State, update::Message}, views::styles::CustomStyles};
+
+use super::root_view::{H1_SIZE, P_SIZE};
+
+const MAX_ENTRIES: usize = 500;
+
+pub fn history_pane(state: &State) -> iced::Element<'_, Message> {
+    let history_text = text(state.language.history())
+        .size(H1_SIZE);
+
+    let filter_input = widget::text_input(state.language.history_filter(), &state.history_filter)
+        .size(P_SIZE)
+        .on_input(Message::HistoryFilterUpdate)
+        .width(iced::Length::Fill);
+
+    let mut event_row = row![].spacing(5);
+    for (event, label) in [
+        (None, state.language.all_events()),
+        (Some(AuditEvent::Connected), state.language.event_connected()),
+        (Some(AuditEvent::Downloaded), state.language.event_downloaded()),
+        (Some(AuditEvent::Aborted), state.language.event_aborted()),
+    ] {
+        let event_button = button(text(label).size(P_SIZE))
+            .on_press(Message::HistoryEventFilter(event))
+            .style(if state.history_event == event { button::primary } else { button::secondary })
+            .width(iced::Length::FillPortion(1));
+        event_row = event_row.push(event_button);
+    }
+
+    let entries = state.audit_log.entries()
+        .iter()
+        .rev()
+        .filter(|entry| entry.matches(state.history_event, &state.history_filter))
+        .collect::<Vec<_>>();
+
+    let mut entry_list = column![].spacing(1);
+    for (indx, entry) in entries.iter().take(MAX_ENTRIES).enumerate() {
+        let event = match entry.event {
+            AuditEvent::Connected => state.language.event_connected(),
+            AuditEvent::Downloaded => state.language.event_downloaded(),
+            AuditEvent::Aborted => state.language.event_aborted(),
+        };
+        let client = match &entry.name {
+            Some(name) => format!("{} ({})", name, entry.ip),
+            None => entry.ip.to_string(),
+        };
+        let file = match &entry.file {
+            Some(file) => format!("{} - {}", file, size_string(entry.bytes)),
+            None => String::new(),
+        };
+
+        let entry = row![
+            text(entry.timestamp.replace('T', " ")).size(P_SIZE).width(iced::Length::FillPortion(3)),
+            text(event).size(P_SIZE).width(iced::Length::FillPortion(2)),
+            text(client).size(P_SIZE).width(iced::Length::FillPortion(3)),
+            text(file).size(P_SIZE).width(iced::Length::FillPortion(4)),
+        ]
+        .spacing(5);
+
+        let entry = container(entry)
+            .padding(5)
+            .width(iced::Length::Fill)
+            .style(CustomStyles::darker_background(if indx & 1 == 0 { 0.9 } else { 0.7 }));
+
+        entry_list = entry_list.push(entry);
+    }
+
+    let count_text = text(state.language.history_entries(entries.len().min(MAX_ENTRIES), entries.len()))
+        .size(P_SIZE);
+
+    let entry_list = iced::widget::scrollable(entry_list)
+        .height(iced::Length::Fill)
+        .style(CustomStyles::scrollable);
+
+    let export_row = row![
+        button(text(state.language.export_csv()).size(P_SIZE))
+            .on_press(Message::ExportHistory(ExportFormat::Csv))
+            .width(iced::Length::FillPortion(1)),
+        button(text(state.language.export_json()).size(P_SIZE))
+            .on_press(Message::ExportHistory(ExportFormat::Json))
+            .width(iced::Length::FillPortion(1)),
+        button(text(state.language.done()).size(P_SIZE))
+            .on_press(Message::ToggleHistory)
+            .width(iced::Length::FillPortion(1)),
+    ]
+    .spacing(5);
+
+    let mut pane = column![
+        history_text,
+        horizontal_rule(5).style(CustomStyles::horizontal_rule),
+        filter_input,
+        event_row,
+        count_text,
+        entry_list,
+    ];
+    if let Some(error) = &state.history_error {
+        pane = pane.push(text(error).size(P_SIZE).style(text::danger));
+    }
+    let pane = pane
+        .push(export_row)
+        .padding(5)
+        .spacing(10)
+        .width(iced::Length::Fill);
+
+    container(pane)
+        .style(CustomStyles::darker_background(0.8))
+        .width(iced::Length::FillPortion(3))
+        .height(iced::Length::FillPortion(1))
+        .padding(5)
+        .into()
+}
@@ -118,10 +118,6 @@ generate_language_functions! {
         English: "You can change the port the server is running on. If you want to serve the files on the internet, make sure to open the port in your router settings."
         Deutsch: "Hier kannst du den Port auf dem der http server läuft änder. Wenn du die Dateien im Internet freigeben möchtest, stelle sicher, dass der Port in den Router-Einstellungen freigegeben ist."
     }
-    locked_port(port) {
-        English: "Cannot change the port while downloads are active. (Active Port: {port})"
-        Deutsch: "Der Port kann nicht geändert werden, solange Downloads aktiv sind. (Aktiver Port: {port})"
-    }
     language {
         English: "Language:"
         Deutsch: "Sprache:"
@@ -181,6 +177,530 @@ generate_language_functions! {
         English: "Use .gitignore"
         Deutsch: "Benutze .gitignore"
     }
+    invites {
+        English: "Invites"
+        Deutsch: "Einladungen"
+    }
+    invite_name {
+        English: "Recipient name"
+        Deutsch: "Name des Empfängers"
+    }
+    create_invite {
+        English: "Create Invite"
+        Deutsch: "Einladung erstellen"
+    }
+    invite_tooltip {
+        English: "Create a personal link for a recipient. Each invite has its own QR code, file selection, expiry and statistics."
+        Deutsch: "Erstellt einen persönlichen Link für einen Empfänger. Jede Einladung hat einen eigenen QR Code, eine eigene Dateiauswahl, ein Ablaufdatum und eigene Statistiken."
+    }
+    public_link {
+        English: "Public Link"
+        Deutsch: "Öffentlicher Link"
+    }
+    invite_files {
+        English: "Files"
+        Deutsch: "Dateien"
+    }
+    done {
+        English: "Done"
+        Deutsch: "Fertig"
+    }
+    all_files {
+        English: "All files"
+        Deutsch: "Alle Dateien"
+    }
+    num_files(n) {
+        English: "{n} files"
+        Deutsch: "{n} Dateien"
+    }
+    invite_expired {
+        English: "Expired"
+        Deutsch: "Abgelaufen"
+    }
+    invite_expires(duration) {
+        English: "Expires in {duration}"
+        Deutsch: "Läuft ab in {duration}"
+    }
+    invite_stats(downloads, size) {
+        English: "{downloads} downloads ({size})"
+        Deutsch: "{downloads} Downloads ({size})"
+    }
+    require_invite {
+        English: "Require an invite"
+        Deutsch: "Einladung erforderlich"
+    }
+    require_invite_tooltip {
+        English: "Only links with a valid invite can see the shared files. The public link stops working."
+        Deutsch: "Nur Links mit einer gültigen Einladung können die geteilten Dateien sehen. Der öffentliche Link funktioniert dann nicht mehr."
+    }
+    ask_new_clients {
+        English: "Ask before allowing new clients"
+        Deutsch: "Neue Clients erst nach Nachfrage zulassen"
+    }
+    ask_new_clients_tooltip {
+        English: "New devices have to be approved before they can see the shared files. Decisions apply to the whole IP address, so allowing a device also allows every other device behind the same router or proxy. Decisions are remembered until the application is closed."
+        Deutsch: "Neue Geräte müssen bestätigt werden, bevor sie die geteilten Dateien sehen können. Entscheidungen gelten für die ganze IP-Adresse, wer ein Gerät erlaubt, erlaubt also auch alle anderen Geräte hinter demselben Router oder Proxy. Entscheidungen werden bis zum Schließen der Anwendung gespeichert."
+    }
+    new_client_title {
+        English: "New Client"
+        Deutsch: "Neuer Client"
+    }
+    new_client(ip) {
+        English: "{ip} wants to access your shared files. Your decision applies to every device using this address."
+        Deutsch: "{ip} möchte auf deine geteilten Dateien zugreifen. Die Entscheidung gilt für alle Geräte mit dieser Adresse."
+    }
+    allow {
+        English: "Allow"
+        Deutsch: "Erlauben"
+    }
+    deny {
+        English: "Deny"
+        Deutsch: "Ablehnen"
+    }
+    settings {
+        English: "Settings"
+        Deutsch: "Einstellungen"
+    }
+    allowlist {
+        English: "Trusted Clients"
+        Deutsch: "Vertrauenswürdige Clients"
+    }
+    allowlist_tooltip {
+        English: "Addresses or ranges (CIDR) on this list are never blocked as external connections and don't need to be approved."
+        Deutsch: "Adressen oder Bereiche (CIDR) auf dieser Liste werden nie als externe Verbindungen blockiert und müssen nicht bestätigt werden."
+    }
+    denylist {
+        English: "Blocked Clients"
+        Deutsch: "Blockierte Clients"
+    }
+    denylist_tooltip {
+        English: "Addresses or ranges (CIDR) on this list can't access the shared files."
+        Deutsch: "Adressen oder Bereiche (CIDR) auf dieser Liste können nicht auf die geteilten Dateien zugreifen."
+    }
+    add {
+        English: "Add"
+        Deutsch: "Hinzufügen"
+    }
+    disconnect {
+        English: "Kick"
+        Deutsch: "Trennen"
+    }
+    block {
+        English: "Block"
+        Deutsch: "Blockieren"
+    }
+    trust {
+        English: "Trust"
+        Deutsch: "Vertrauen"
+    }
+    limits {
+        English: "Limits"
+        Deutsch: "Limits"
+    }
+    limits_tooltip {
+        English: "Downloads above the connections per IP wait for a free slot for up to 30 seconds before they are rejected with status 429. Other requests above these limits are rejected right away. Leave a field empty to disable the limit."
+        Deutsch: "Downloads über den Verbindungen pro IP warten bis zu 30 Sekunden auf einen freien Platz, bevor sie mit Status 429 abgelehnt werden. Andere Anfragen über diesen Limits werden sofort abgelehnt. Lasse ein Feld leer, um das Limit zu deaktivieren."
+    }
+    connections_per_ip {
+        English: "Concurrent downloads per IP"
+        Deutsch: "Gleichzeitige Downloads pro IP"
+    }
+    max_clients {
+        English: "Maximum number of clients"
+        Deutsch: "Maximale Anzahl Clients"
+    }
+    requests_per_second {
+        English: "Requests per second per IP"
+        Deutsch: "Anfragen pro Sekunde pro IP"
+    }
+    rejected_requests {
+        English: "Rejected Requests"
+        Deutsch: "Abgelehnte Anfragen"
+    }
+    rejected_requests_tooltip(connections, clients, requests) {
+        English: "Too many downloads: {connections}\nToo many clients: {clients}\nToo many requests: {requests}"
+        Deutsch: "Zu viele Downloads: {connections}\nZu viele Clients: {clients}\nZu viele Anfragen: {requests}"
+    }
+    base_path {
+        English: "Base Path"
+        Deutsch: "Basispfad"
+    }
+    base_path_tooltip {
+        English: "Serve all pages below this path, e.g. /share when running behind a reverse proxy. Press Enter to apply."
+        Deutsch: "Stellt alle Seiten unter diesem Pfad bereit, z.B. /share hinter einem Reverse Proxy. Drücke Enter zum Übernehmen."
+    }
+    trusted_proxies {
+        English: "Trusted Proxies"
+        Deutsch: "Vertrauenswürdige Proxies"
+    }
+    trusted_proxies_tooltip {
+        English: "Requests from these addresses may set the client address with the X-Forwarded-For or Forwarded header."
+        Deutsch: "Anfragen von diesen Adressen dürfen die Client Adresse über den X-Forwarded-For oder Forwarded Header setzen."
+    }
+    visible_for(name) {
+        English: "Visible for {name}"
+        Deutsch: "Sichtbar für {name}"
+    }
+    local_name {
+        English: "Local Name"
+        Deutsch: "Lokaler Name"
+    }
+    local_name_tooltip {
+        English: "This address is announced on the local network via mDNS and works on most devices without typing the IP address."
+        Deutsch: "Diese Adresse wird im lokalen Netzwerk per mDNS angekündigt und funktioniert auf den meisten Geräten ohne die IP Adresse einzutippen."
+    }
+    nearby_shares {
+        English: "Nearby Shares"
+        Deutsch: "Freigaben in der Nähe"
+    }
+    no_nearby_shares {
+        English: "No other instances found on this network."
+        Deutsch: "Keine anderen Instanzen in diesem Netzwerk gefunden."
+    }
+    network_interfaces {
+        English: "Network Interfaces"
+        Deutsch: "Netzwerkschnittstellen"
+    }
+    network_interfaces_tooltip {
+        English: "Choose the addresses the server listens on. Automatic picks the default route, All listens on every interface."
+        Deutsch: "Wähle die Adressen, auf denen der Server erreichbar ist. Automatisch nutzt die Standardroute, Alle lauscht auf jeder Schnittstelle."
+    }
+    automatic {
+        English: "Automatic"
+        Deutsch: "Automatisch"
+    }
+    all_interfaces {
+        English: "All"
+        Deutsch: "Alle"
+    }
+    server_stopped {
+        English: "Server stopped"
+        Deutsch: "Server gestoppt"
+    }
+    server_starting {
+        English: "Server starting..."
+        Deutsch: "Server startet..."
+    }
+    server_running {
+        English: "Server running on:"
+        Deutsch: "Server läuft auf:"
+    }
+    server_bind_failed(port, error) {
+        English: "Could not start the server on port {port}: {error}"
+        Deutsch: "Der Server konnte auf Port {port} nicht gestartet werden: {error}"
+    }
+    port_fallback {
+        English: "Use the next free port if the port is taken"
+        Deutsch: "Nächsten freien Port nutzen, falls der Port belegt ist"
+    }
+    performance {
+        English: "Performance"
+        Deutsch: "Leistung"
+    }
+    run_benchmark {
+        English: "Measure Throughput"
+        Deutsch: "Durchsatz messen"
+    }
+    benchmark_running {
+        English: "Measuring..."
+        Deutsch: "Messe..."
+    }
+    benchmark_result(throughput) {
+        English: "Loopback throughput: {throughput}/s"
+        Deutsch: "Lokaler Durchsatz: {throughput}/s"
+    }
+    benchmark_failed(error) {
+        English: "Benchmark failed: {error}"
+        Deutsch: "Messung fehlgeschlagen: {error}"
+    }
+    history {
+        English: "History"
+        Deutsch: "Verlauf"
+    }
+    history_filter {
+        English: "Filter by IP, name, file or date"
+        Deutsch: "Nach IP, Name, Datei oder Datum filtern"
+    }
+    history_entries(shown, total) {
+        English: "Showing {shown} of {total} entries"
+        Deutsch: "{shown} von {total} Einträgen"
+    }
+    all_events {
+        English: "All"
+        Deutsch: "Alle"
+    }
+    event_connected {
+        English: "Connected"
+        Deutsch: "Verbunden"
+    }
+    event_downloaded {
+        English: "Downloaded"
+        Deutsch: "Heruntergeladen"
+    }
+    event_aborted {
+        English: "Aborted"
+        Deutsch: "Abgebrochen"
+    }
+    export_csv {
+        English: "Export CSV"
+        Deutsch: "Als CSV exportieren"
+    }
+    export_json {
+        English: "Export JSON"
+        Deutsch: "Als JSON exportieren"
+    }
+    history_export_failed(error) {
+        English: "Couldn't export the history: {error}"
+        Deutsch: "Verlauf konnte nicht exportiert werden: {error}"
+    }
+    history_write_failed(error) {
+        English: "Couldn't write to the history file, new entries are only kept until the app closes: {error}"
+        Deutsch: "Verlaufsdatei konnte nicht geschrieben werden, neue Einträge bleiben nur bis zum Beenden erhalten: {error}"
+    }
+    access_log {
+        English: "Access log"
+        Deutsch: "Zugriffsprotokoll"
+    }
+    access_log_tooltip(path) {
+        English: "Writes every request in Combined Log Format to {path}, so it can be read by tools like goaccess or fail2ban."
+        Deutsch: "Schreibt jede Anfrage im Combined Log Format nach {path}, damit Werkzeuge wie goaccess oder fail2ban sie auswerten können."
+    }
+    enable_access_log {
+        English: "Write access log"
+        Deutsch: "Zugriffsprotokoll schreiben"
+    }
+    log_rotation {
+        English: "Rotation"
+        Deutsch: "Rotation"
+    }
+    rotate_daily {
+        English: "Daily"
+        Deutsch: "Täglich"
+    }
+    rotate_by_size {
+        English: "By size"
+        Deutsch: "Nach Größe"
+    }
+    max_log_size {
+        English: "Maximum size (MB)"
+        Deutsch: "Maximale Größe (MB)"
+    }
+    metrics {
+        English: "Metrics"
+        Deutsch: "Metriken"
+    }
+    metrics_tooltip {
+        English: "Serves Prometheus metrics at /metrics. Leave the port empty to use the server port."
+        Deutsch: "Stellt Prometheus-Metriken unter /metrics bereit. Ohne eigenen Port wird der Server-Port verwendet."
+    }
+    enable_metrics {
+        English: "Expose /metrics"
+        Deutsch: "/metrics bereitstellen"
+    }
+    metrics_local_only {
+        English: "Only allow requests from this device"
+        Deutsch: "Nur Anfragen von diesem Gerät erlauben"
+    }
+    metrics_port {
+        English: "Separate port"
+        Deutsch: "Eigener Port"
+    }
+    metrics_bind_failed(port, error) {
+        English: "Metrics port {port} is unavailable: {error}"
+        Deutsch: "Metrik-Port {port} ist nicht verfügbar: {error}"
+    }
+    details {
+        English: "Details"
+        Deutsch: "Details"
+    }
+    no_transfers {
+        English: "No transfers yet"
+        Deutsch: "Noch keine Übertragungen"
+    }
+    transfer_done {
+        English: "Done"
+        Deutsch: "Fertig"
+    }
+    transfer_aborted {
+        English: "Aborted"
+        Deutsch: "Abgebrochen"
+    }
+    transfer_details(sent, size, duration, started) {
+        English: "{sent} of {size} in {duration}, started {started} ago"
+        Deutsch: "{sent} von {size} in {duration}, vor {started} gestartet"
+    }
+    resolve_hostnames {
+        English: "Resolve client hostnames"
+        Deutsch: "Hostnamen der Clients auflösen"
+    }
+    resolve_hostnames_tooltip {
+        English: "Looks up the hostname of each client via reverse DNS or mDNS to make devices easier to tell apart."
+        Deutsch: "Ermittelt den Hostnamen jedes Clients per Reverse-DNS oder mDNS, damit Geräte leichter zu unterscheiden sind."
+    }
+    notifications {
+        English: "Notifications"
+        Deutsch: "Benachrichtigungen"
+    }
+    notification_new_client {
+        English: "New client connected"
+        Deutsch: "Neuer Client verbunden"
+    }
+    notification_new_client_body(client) {
+        English: "{client} opened the download page"
+        Deutsch: "{client} hat die Downloadseite geöffnet"
+    }
+    notification_download_completed {
+        English: "Download completed"
+        Deutsch: "Download abgeschlossen"
+    }
+    notification_download_completed_body(client, file) {
+        English: "{client} downloaded {file}"
+        Deutsch: "{client} hat {file} heruntergeladen"
+    }
+    notification_all_files_downloaded {
+        English: "All files downloaded"
+        Deutsch: "Alle Dateien heruntergeladen"
+    }
+    notification_all_files_downloaded_body(client) {
+        English: "{client} downloaded every shared file"
+        Deutsch: "{client} hat alle geteilten Dateien heruntergeladen"
+    }
+    notification_zip_finished {
+        English: "Zip finished"
+        Deutsch: "Zip fertig"
+    }
+    notification_zip_finished_body(name) {
+        English: "{name} is compressed and ready to share"
+        Deutsch: "{name} ist komprimiert und bereit zum Teilen"
+    }
+    hooks {
+        English: "Hooks"
+        Deutsch: "Hooks"
+    }
+    hooks_tooltip {
+        English: "Run a shell command or POST JSON to a local http:// URL when an event happens. Commands receive FILESHARE_EVENT, FILESHARE_FILE, FILESHARE_FILE_NAME, FILESHARE_CLIENT_IP, FILESHARE_CLIENT_NAME and FILESHARE_BYTES as environment variables. Hooks are stopped after 30 seconds."
+        Deutsch: "Führt einen Shell-Befehl aus oder sendet JSON per POST an eine lokale http://-URL, wenn ein Ereignis eintritt. Befehle erhalten FILESHARE_EVENT, FILESHARE_FILE, FILESHARE_FILE_NAME, FILESHARE_CLIENT_IP, FILESHARE_CLIENT_NAME und FILESHARE_BYTES als Umgebungsvariablen. Hooks werden nach 30 Sekunden abgebrochen."
+    }
+    hook_downloaded {
+        English: "Downloaded"
+        Deutsch: "Heruntergeladen"
+    }
+    hook_client_connected {
+        English: "Client connected"
+        Deutsch: "Client verbunden"
+    }
+    hook_zip_done {
+        English: "Zip done"
+        Deutsch: "Zip fertig"
+    }
+    hook_command {
+        English: "Command"
+        Deutsch: "Befehl"
+    }
+    hook_webhook {
+        English: "Webhook"
+        Deutsch: "Webhook"
+    }
+    restore_shared {
+        English: "Restore on launch"
+        Deutsch: "Beim Start wiederherstellen"
+    }
+    restore_shared_tooltip {
+        English: "Remember the shared files and zipped folders and share them again when Fileshare starts."
+        Deutsch: "Geteilte Dateien und gezippte Ordner merken und beim Start von Fileshare erneut teilen."
+    }
+    missing_files(count) {
+        English: "Missing ({count})"
+        Deutsch: "Fehlend ({count})"
+    }
+    missing_file_tooltip {
+        English: "This entry no longer exists and is not shared."
+        Deutsch: "Dieser Eintrag existiert nicht mehr und wird nicht geteilt."
+    }
+    retry {
+        English: "Retry"
+        Deutsch: "Erneut versuchen"
+    }
+    profile {
+        English: "Profile:"
+        Deutsch: "Profil:"
+    }
+    profiles {
+        English: "Profiles"
+        Deutsch: "Profile"
+    }
+    profiles_tooltip {
+        English: "A profile bundles the port, access rules and limits, interfaces, theme and shared files. Switch between profiles from the footer."
+        Deutsch: "Ein Profil bündelt Port, Zugriffsregeln und Limits, Schnittstellen, Design und geteilte Dateien. Profile lassen sich in der Fußleiste wechseln."
+    }
+    profile_name {
+        English: "Profile name, e.g. Office LAN"
+        Deutsch: "Profilname, z.B. Büro-LAN"
+    }
+    profile_summary(port, files) {
+        English: "Port {port}, {files} shared entries"
+        Deutsch: "Port {port}, {files} geteilte Einträge"
+    }
+    save_profile {
+        English: "Save current"
+        Deutsch: "Aktuelle speichern"
+    }
+    apply {
+        English: "Apply"
+        Deutsch: "Anwenden"
+    }
+    import {
+        English: "Import"
+        Deutsch: "Importieren"
+    }
+    export {
+        English: "Export"
+        Deutsch: "Exportieren"
+    }
+    profile_import_failed(error) {
+        English: "Couldn't import profiles: {error}"
+        Deutsch: "Profile konnten nicht importiert werden: {error}"
+    }
+    profile_export_failed(error) {
+        English: "Couldn't export profiles: {error}"
+        Deutsch: "Profile konnten nicht exportiert werden: {error}"
+    }
+    storage {
+        English: "Storage"
+        Deutsch: "Speicherorte"
+    }
+    config_folder {
+        English: "Config"
+        Deutsch: "Konfiguration"
+    }
+    data_folder {
+        English: "Data"
+        Deutsch: "Daten"
+    }
+    cache_folder {
+        English: "Cache"
+        Deutsch: "Cache"
+    }
+    portable_mode {
+        English: "Portable mode"
+        Deutsch: "Portabler Modus"
+    }
+    portable_mode_tooltip {
+        English: "Keeps config, logs and temporary files next to the executable by placing a 'portable' marker file there. Existing settings are copied over on the next start."
+        Deutsch: "Speichert Konfiguration, Logs und temporäre Dateien neben der Programmdatei, indem dort eine 'portable'-Markierungsdatei angelegt wird. Bestehende Einstellungen werden beim nächsten Start übernommen."
+    }
+    portable_mode_failed(error) {
+        English: "Couldn't change portable mode: {error}"
+        Deutsch: "Portabler Modus konnte nicht geändert werden: {error}"
+    }
+    instance_listen_failed(error) {
+        English: "Files opened from outside and 'fileshare ctl' can't reach this window: {error}"
+        Deutsch: "Von außen geöffnete Dateien und 'fileshare ctl' erreichen dieses Fenster nicht: {error}"
+    }
+    restart_required {
+        English: "Takes effect after a restart."
+        Deutsch: "Wird nach einem Neustart wirksam."
+    }
 }
 
 impl std::fmt::Display for Language {
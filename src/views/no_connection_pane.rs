@@ -3,7 +3,7 @@ use crate::{state::state::State, views::styles::CustomStyles, state::update::Mes
 
 use super::root_view::{DOWNLOAD_PANE_WIDTH, H1_SIZE, H2_SIZE};
 
-pub fn no_connection_pane(_state: &State, height: Length)  -> iced::Element<Message> {
+pub fn no_connection_pane(_state: &State, height: Length)  -> iced::Element<'_, Message> {
     let collumn = column![
         text!("Network Error")
             .size(H1_SIZE)
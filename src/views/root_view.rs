@@ -1,8 +1,7 @@
 use iced::widget::{button, column, container, row, stack};
-use local_ip_address::local_ip;
 use crate::{state::state::State, state::update::Message};
 
-use super::{connection_info_pane::connection_info_pane, download_pane::download_pane, footer_pane::footer_pane, no_connection_pane::no_connection_pane, upload_pane::upload_pane};
+use super::{approval_pane::approval_pane, connection_info_pane::connection_info_pane, download_pane::download_pane, footer_pane::footer_pane, history_pane::history_pane, no_connection_pane::no_connection_pane, settings_pane::settings_pane, upload_pane::upload_pane};
 
 pub const H1_SIZE: u16 = 30;
 pub const H2_SIZE: u16 = 20;
@@ -10,7 +9,7 @@ pub const P_SIZE: u16 = 13;
 pub const CONNECTION_PANE_WIDTH: f32 = 250.0;
 pub const DOWNLOAD_PANE_WIDTH: f32 = 250.0;
 
-pub fn view(state: &State) -> iced::Element<Message> {
+pub fn view(state: &State) -> iced::Element<'_, Message> {
     let max_width = 1100.0 + if state.show_connections { CONNECTION_PANE_WIDTH } else { 0.0 };
 
     let mut main = row![]
@@ -26,9 +25,15 @@ pub fn view(state: &State) -> iced::Element<Message> {
         main = main.push(connection_info_pane);
     }
 
-    main = main.push(upload_pane(state));
+    if state.show_settings {
+        main = main.push(settings_pane(state));
+    } else if state.show_history {
+        main = main.push(history_pane(state));
+    } else {
+        main = main.push(upload_pane(state));
+    }
     
-    if !local_ip().is_ok_and(|ip| state.ip_adress.is_some_and(|ip_2| ip == ip_2))  {
+    if state.ip_adress.is_none() {
         main = main.push(no_connection_pane(state, iced::Length::Fill));
     } else if !state.file_manager.get_view().is_empty() {
         main = main.push(download_pane(state));
@@ -51,7 +56,10 @@ let main = column!(
     footer_pane(state)
 );
 
-main.into()
+match approval_pane(state) {
+    Some(approval_pane) => stack!(main, approval_pane).into(),
+    None => main.into(),
+}
 }
 
 fn toggle_button(state: &State) -> container::Container<'_, Message> {
@@ -61,7 +69,6 @@ fn toggle_button(state: &State) -> container::Container<'_, Message> {
     let toggle_connection_view_buton = button(iced::widget::text(connections_tab_text).size(size))
         .on_press(Message::ToggleConnectionsView)
         .padding(5);
-    let toggle_connection_view_buton = container(toggle_connection_view_buton)
-        .padding(padding);
-    toggle_connection_view_buton
+    container(toggle_connection_view_buton)
+        .padding(padding)
 }
\ No newline at end of file
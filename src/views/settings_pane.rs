@@ -0,0 +1,660 @@
+use iced::widget::{self, button, checkbox, column, container, horizontal_rule, row, text, tooltip};
+use crate::{server::{access_log::LogRotation, rate_limiter::LimitKind, webpage_service::size_string}, state::{access_manager::{parse_rule, AccessList}, hooks::{HookEvent, HookKind}, network_manager::InterfaceSelection, notifications::NotificationEvent, paths::paths, state::State, update::Message}, views::styles::{color_multiply, CustomStyles}};
+
+use super::root_view::{H1_SIZE, H2_SIZE, P_SIZE};
+
+pub fn settings_pane(state: &State) -> iced::Element<'_, Message> {
+    let settings_text = text(state.language.settings())
+        .size(H1_SIZE);
+
+    let rules = state.access_manager.rules();
+
+    let allowlist = rule_list(
+        state,
+        AccessList::Allow,
+        state.language.allowlist(),
+        state.language.allowlist_tooltip(),
+        &state.allowlist_buffer,
+        rules.allow,
+    );
+
+    let denylist = rule_list(
+        state,
+        AccessList::Deny,
+        state.language.denylist(),
+        state.language.denylist_tooltip(),
+        &state.denylist_buffer,
+        rules.deny,
+    );
+
+    let pane = column![
+        settings_text,
+        horizontal_rule(5).style(CustomStyles::horizontal_rule),
+        profiles_section(state),
+        interfaces_section(state),
+        allowlist,
+        denylist,
+        limits_section(state),
+        access_log_section(state),
+        metrics_section(state),
+        notifications_section(state),
+        hooks_section(state),
+        performance_section(state),
+        storage_section(state),
+        reverse_proxy_section(state, rules.proxies),
+    ]
+    .padding(5)
+    .spacing(10)
+    .width(iced::Length::Fill)
+    .align_x(iced::alignment::Horizontal::Center);
+
+    let pane = iced::widget::scrollable(pane)
+        .height(iced::Length::Fill)
+        .style(CustomStyles::scrollable);
+
+    let close_button = button(state.language.done())
+        .on_press(Message::ToggleSettings)
+        .width(iced::Length::Fill);
+
+    let pane = column![pane, close_button]
+        .padding(5)
+        .spacing(10);
+
+    container(pane)
+        .style(CustomStyles::darker_background(0.8))
+        .width(iced::Length::FillPortion(3))
+        .height(iced::Length::FillPortion(1))
+        .padding(5)
+        .into()
+}
+
+fn interfaces_section(state: &State) -> iced::Element<'_, Message> {
+    let title = tooltip(
+        text(state.language.network_interfaces()).size(H2_SIZE),
+        container(text(state.language.network_interfaces_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let selection = state.network_manager.selection();
+    let automatic_button = button(text(state.language.automatic()).size(P_SIZE))
+        .on_press(Message::InterfaceSelectionChanged(InterfaceSelection::Automatic))
+        .style(if *selection == InterfaceSelection::Automatic { button::primary } else { button::secondary })
+        .width(iced::Length::FillPortion(1));
+
+    let all_button = button(text(state.language.all_interfaces()).size(P_SIZE))
+        .on_press(Message::InterfaceSelectionChanged(InterfaceSelection::All))
+        .style(if *selection == InterfaceSelection::All { button::primary } else { button::secondary })
+        .width(iced::Length::FillPortion(1));
+
+    let mut section = column![
+        title,
+        row![automatic_button, all_button].spacing(5),
+    ]
+    .spacing(10)
+    .padding(10);
+
+    for interface in state.network_manager.interfaces() {
+        let ip = interface.ip;
+        let interface = checkbox(interface.to_string(), state.network_manager.is_selected(ip))
+            .on_toggle(move |_| Message::ToggleInterface(ip))
+            .size(16)
+            .text_size(P_SIZE)
+            .width(iced::Length::Fill);
+
+        section = section.push(interface);
+    }
+
+    let port_fallback = checkbox(state.language.port_fallback(), state.port_fallback)
+        .on_toggle(Message::PortFallback)
+        .size(16)
+        .text_size(P_SIZE)
+        .width(iced::Length::Fill);
+
+    section = section.push(horizontal_rule(5).style(CustomStyles::horizontal_rule));
+    section = section.push(port_fallback);
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn reverse_proxy_section(state: &State, proxies: Vec<ipnet::IpNet>) -> iced::Element<'_, Message> {
+    let base_path_title = tooltip(
+        text(state.language.base_path()).size(H2_SIZE),
+        container(text(state.language.base_path_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let mut base_path_input = widget::text_input("/share", &state.base_path_buffer)
+        .size(P_SIZE)
+        .on_input(Message::BasePathUpdate)
+        .on_submit(Message::ChangeBasePath);
+
+    if state.base_path_buffer != state.base_path {
+        base_path_input = base_path_input.style(CustomStyles::textfield_background(color_multiply(state.theme.get().palette().primary, 0.8)));
+    }
+
+    let base_path = column![base_path_title, base_path_input]
+        .spacing(10)
+        .padding(10);
+
+    let base_path = container(base_path)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    let base_path = container(base_path)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0);
+
+    let proxies = rule_list(
+        state,
+        AccessList::Proxy,
+        state.language.trusted_proxies(),
+        state.language.trusted_proxies_tooltip(),
+        &state.proxy_buffer,
+        proxies,
+    );
+
+    column![base_path, proxies]
+        .spacing(10)
+        .into()
+}
+
+fn storage_section(state: &State) -> iced::Element<'_, Message> {
+    let title = text(state.language.storage()).size(H2_SIZE);
+    let mut section = column![title].spacing(10).padding(10);
+
+    let paths = paths();
+    for (label, path) in [
+        (state.language.config_folder(), &paths.config),
+        (state.language.data_folder(), &paths.data),
+        (state.language.cache_folder(), &paths.cache),
+    ] {
+        section = section.push(row![
+            text(label).size(P_SIZE).width(iced::Length::FillPortion(1)),
+            text(path.to_string_lossy()).size(P_SIZE).width(iced::Length::FillPortion(4)),
+            button(text(state.language.open()).size(P_SIZE)).on_press(Message::OpenFolder(path.clone())),
+        ]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center));
+    }
+
+    let portable = checkbox(state.language.portable_mode(), state.portable)
+        .on_toggle(Message::PortableMode)
+        .size(16)
+        .text_size(P_SIZE)
+        .width(iced::Length::Fill);
+
+    let portable = tooltip(
+        portable,
+        container(text(state.language.portable_mode_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+    section = section.push(portable);
+
+    if state.portable != paths.portable {
+        section = section.push(text(state.language.restart_required()).size(P_SIZE).style(text::primary));
+    }
+    if let Some(error) = &state.storage_error {
+        section = section.push(text(error).size(P_SIZE).style(text::danger));
+    }
+    if let Some(error) = &state.instance_error {
+        section = section.push(text(error).size(P_SIZE).style(text::danger));
+    }
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn performance_section(state: &State) -> iced::Element<'_, Message> {
+    let title = text(state.language.performance()).size(H2_SIZE);
+    let mut section = column![title].spacing(10).padding(10);
+
+    let benchmark_button = match state.benchmark_running {
+        true => button(text(state.language.benchmark_running()).size(P_SIZE)),
+        false => button(text(state.language.run_benchmark()).size(P_SIZE)).on_press(Message::RunBenchmark),
+    };
+
+    let benchmark_text = match &state.benchmark {
+        Some(Ok(result)) => state.language.benchmark_result(size_string(result.throughput)),
+        Some(Err(error)) => state.language.benchmark_failed(error),
+        None => String::new(),
+    };
+
+    section = section.push(
+        row![benchmark_button, text(benchmark_text).size(P_SIZE).width(iced::Length::Fill)]
+            .spacing(10)
+            .align_y(iced::alignment::Vertical::Center)
+    );
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn limits_section(state: &State) -> iced::Element<'_, Message> {
+    let title = tooltip(
+        text(state.language.limits()).size(H2_SIZE),
+        container(text(state.language.limits_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let limits = state.rate_limiter.limits();
+    let mut section = column![title].spacing(10).padding(10);
+
+    for (kind, label) in [
+        (LimitKind::ConnectionsPerIp, state.language.connections_per_ip()),
+        (LimitKind::Clients, state.language.max_clients()),
+        (LimitKind::RequestsPerSecond, state.language.requests_per_second()),
+    ] {
+        let value = match limits.get(kind) {
+            0 => String::new(),
+            n => n.to_string(),
+        };
+        let input = widget::text_input("∞", &value)
+            .size(P_SIZE)
+            .width(iced::Length::Fixed(100.0))
+            .on_input(move |input| Message::LimitChanged(kind, input));
+
+        let limit_row = row![text(label).size(P_SIZE).width(iced::Length::Fill), input]
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center);
+
+        section = section.push(limit_row);
+    }
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn access_log_section(state: &State) -> iced::Element<'_, Message> {
+    let title = tooltip(
+        text(state.language.access_log()).size(H2_SIZE),
+        container(text(state.language.access_log_tooltip(state.access_log.path().to_string_lossy())).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let config = state.access_log.config();
+    let enabled = checkbox(state.language.enable_access_log(), config.enabled)
+        .on_toggle(Message::AccessLogEnabled)
+        .size(16)
+        .text_size(P_SIZE)
+        .width(iced::Length::Fill);
+
+    let mut rotation_row = row![text(state.language.log_rotation()).size(P_SIZE).width(iced::Length::Fill)]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+    for (rotation, label) in [
+        (LogRotation::Daily, state.language.rotate_daily()),
+        (LogRotation::Size, state.language.rotate_by_size()),
+    ] {
+        let rotation_button = button(text(label).size(P_SIZE))
+            .on_press(Message::AccessLogRotation(rotation))
+            .style(if config.rotation == rotation { button::primary } else { button::secondary });
+        rotation_row = rotation_row.push(rotation_button);
+    }
+
+    let mut section = column![title, enabled, rotation_row].spacing(10).padding(10);
+
+    if config.rotation == LogRotation::Size {
+        let value = match config.max_size_mb {
+            0 => String::new(),
+            n => n.to_string(),
+        };
+        let input = widget::text_input("1", &value)
+            .size(P_SIZE)
+            .width(iced::Length::Fixed(100.0))
+            .on_input(Message::AccessLogMaxSize);
+
+        section = section.push(
+            row![text(state.language.max_log_size()).size(P_SIZE).width(iced::Length::Fill), input]
+                .spacing(5)
+                .align_y(iced::alignment::Vertical::Center)
+        );
+    }
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn metrics_section(state: &State) -> iced::Element<'_, Message> {
+    let title = tooltip(
+        text(state.language.metrics()).size(H2_SIZE),
+        container(text(state.language.metrics_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let config = state.metrics.config();
+    let enabled = checkbox(state.language.enable_metrics(), config.enabled)
+        .on_toggle(Message::MetricsEnabled)
+        .size(16)
+        .text_size(P_SIZE)
+        .width(iced::Length::Fill);
+
+    let local_only = checkbox(state.language.metrics_local_only(), config.local_only)
+        .on_toggle(Message::MetricsLocalOnly)
+        .size(16)
+        .text_size(P_SIZE)
+        .width(iced::Length::Fill);
+
+    let port_input = widget::text_input(&state.port_buffer, &state.metrics_port_buffer)
+        .size(P_SIZE)
+        .width(iced::Length::Fixed(100.0))
+        .on_input(Message::MetricsPortUpdate)
+        .on_submit(Message::ChangeMetricsPort);
+
+    let port_row = row![text(state.language.metrics_port()).size(P_SIZE).width(iced::Length::Fill), port_input]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+
+    let mut section = column![title, enabled, local_only, port_row].spacing(10).padding(10);
+
+    if config.enabled {
+        let url = match config.port {
+            Some(port) => format!("http://localhost:{}/metrics", port),
+            None => format!("http://localhost:{}{}/metrics", state.port, state.base_path),
+        };
+        section = section.push(text(url).size(P_SIZE));
+    }
+    if let Some(error) = &state.metrics_error {
+        section = section.push(text(error).size(P_SIZE).style(text::danger));
+    }
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn notifications_section(state: &State) -> iced::Element<'_, Message> {
+    let title = text(state.language.notifications()).size(H2_SIZE);
+    let mut section = column![title].spacing(10).padding(10);
+
+    for (event, label) in [
+        (NotificationEvent::NewClient, state.language.notification_new_client()),
+        (NotificationEvent::DownloadCompleted, state.language.notification_download_completed()),
+        (NotificationEvent::AllFilesDownloaded, state.language.notification_all_files_downloaded()),
+        (NotificationEvent::ZipFinished, state.language.notification_zip_finished()),
+    ] {
+        let toggle = checkbox(label, state.notifications.get(event))
+            .on_toggle(move |enabled| Message::NotificationToggled(event, enabled))
+            .size(16)
+            .text_size(P_SIZE)
+            .width(iced::Length::Fill);
+        section = section.push(toggle);
+    }
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn profiles_section(state: &State) -> iced::Element<'_, Message> {
+    let title = tooltip(
+        text(state.language.profiles()).size(H2_SIZE),
+        container(text(state.language.profiles_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let mut section = column![title].spacing(10).padding(10);
+
+    for profile in state.profiles.iter() {
+        let name = text(&profile.name).size(P_SIZE).width(iced::Length::FillPortion(2));
+        let name = match state.active_profile.as_ref() == Some(&profile.name) {
+            true => name.style(text::success),
+            false => name,
+        };
+        let profile_row = row![
+            name,
+            text(state.language.profile_summary(profile.port, profile.shared.len())).size(P_SIZE).width(iced::Length::FillPortion(3)),
+            button(text(state.language.apply()).size(P_SIZE)).on_press(Message::ProfileSelected(profile.name.clone())),
+            button(text("X").size(P_SIZE)).on_press(Message::DeleteProfile(profile.name.clone())).style(button::danger),
+        ]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+        section = section.push(profile_row);
+    }
+
+    let input = widget::text_input(state.language.profile_name(), &state.profile_name_buffer)
+        .size(P_SIZE)
+        .on_input(Message::ProfileNameUpdate)
+        .on_submit(Message::SaveProfile)
+        .width(iced::Length::Fill);
+
+    let input_row = row![
+        input,
+        button(text(state.language.save_profile()).size(P_SIZE)).on_press(Message::SaveProfile),
+        button(text(state.language.import()).size(P_SIZE)).on_press(Message::ImportProfiles),
+        button(text(state.language.export()).size(P_SIZE)).on_press_maybe((!state.profiles.is_empty()).then_some(Message::ExportProfiles)),
+    ]
+    .spacing(5)
+    .align_y(iced::alignment::Vertical::Center);
+
+    section = section.push(input_row);
+
+    if let Some(error) = &state.profile_error {
+        section = section.push(text(error).size(P_SIZE).style(text::danger));
+    }
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn hooks_section(state: &State) -> iced::Element<'_, Message> {
+    let title = tooltip(
+        text(state.language.hooks()).size(H2_SIZE),
+        container(text(state.language.hooks_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let event_label = |event: HookEvent| match event {
+        HookEvent::Downloaded => state.language.hook_downloaded(),
+        HookEvent::ClientConnected => state.language.hook_client_connected(),
+        HookEvent::ZipDone => state.language.hook_zip_done(),
+    };
+    let kind_label = |kind: HookKind| match kind {
+        HookKind::Command => state.language.hook_command(),
+        HookKind::Webhook => state.language.hook_webhook(),
+    };
+
+    let mut section = column![title].spacing(10).padding(10);
+
+    for (index, hook) in state.hooks.iter().enumerate() {
+        let hook_row = row![
+            text(format!("{} · {}", event_label(hook.event), kind_label(hook.kind))).size(P_SIZE).width(iced::Length::FillPortion(2)),
+            text(&hook.target).size(P_SIZE).width(iced::Length::FillPortion(3)),
+            button(text("X").size(P_SIZE)).on_press(Message::RemoveHook(index)).style(button::danger),
+        ]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+        section = section.push(hook_row);
+    }
+
+    let mut event_row = row![].spacing(5);
+    for event in [HookEvent::Downloaded, HookEvent::ClientConnected, HookEvent::ZipDone] {
+        event_row = event_row.push(
+            button(text(event_label(event)).size(P_SIZE))
+                .on_press(Message::HookEventSelected(event))
+                .style(if state.hook_event == event { button::primary } else { button::secondary })
+                .width(iced::Length::FillPortion(1))
+        );
+    }
+
+    let mut kind_row = row![].spacing(5);
+    for kind in [HookKind::Command, HookKind::Webhook] {
+        kind_row = kind_row.push(
+            button(text(kind_label(kind)).size(P_SIZE))
+                .on_press(Message::HookKindSelected(kind))
+                .style(if state.hook_kind == kind { button::primary } else { button::secondary })
+                .width(iced::Length::FillPortion(1))
+        );
+    }
+
+    let placeholder = match state.hook_kind {
+        HookKind::Command => "rm \"$FILESHARE_FILE\"",
+        HookKind::Webhook => "http://localhost:8000/hook",
+    };
+    let input = widget::text_input(placeholder, &state.hook_buffer)
+        .size(P_SIZE)
+        .on_input(Message::HookInputUpdate)
+        .on_submit(Message::AddHook)
+        .width(iced::Length::Fill);
+
+    let input_row = row![input, button(text(state.language.add()).size(P_SIZE)).on_press(Message::AddHook)]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+
+    section = section.push(event_row).push(kind_row).push(input_row);
+
+    for result in state.hook_results.iter() {
+        let (outcome, style): (&str, fn(&iced::Theme) -> text::Style) = match &result.outcome {
+            Ok(output) => (output, text::success),
+            Err(error) => (error, text::danger),
+        };
+        section = section.push(
+            text(format!("{} {} · {}: {}", result.timestamp, event_label(result.hook.event), result.hook.target, outcome))
+                .size(12)
+                .style(style)
+        );
+    }
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
+
+fn rule_list<'a>(
+    state: &'a State,
+    list: AccessList,
+    title: &'a str,
+    tooltip_text: &'a str,
+    buffer: &'a str,
+    entries: Vec<ipnet::IpNet>,
+) -> iced::Element<'a, Message> {
+    let title = tooltip(
+        text(title).size(H2_SIZE),
+        container(text(tooltip_text).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(300.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let valid = parse_rule(buffer).is_some();
+    let mut input = widget::text_input("192.168.0.0/24", buffer)
+        .size(P_SIZE)
+        .on_input(move |input| Message::RuleInputUpdate(list, input));
+    if valid {
+        input = input.on_submit(Message::AddRule(list));
+    } else if !buffer.is_empty() {
+        input = input.style(CustomStyles::textfield_background(state.theme.get().palette().danger));
+    }
+
+    let add_button = button(state.language.add())
+        .on_press_maybe(valid.then_some(Message::AddRule(list)));
+
+    let input_row = row![input, add_button]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+
+    let mut entry_list = column![].spacing(1);
+    for (indx, net) in entries.into_iter().enumerate() {
+        let entry = row![
+            text!("{}", net).size(P_SIZE).width(iced::Length::Fill),
+            button(state.language.delete()).on_press(Message::RemoveRule(list, net)),
+        ]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center);
+
+        let entry = container(entry)
+            .padding(5)
+            .style(CustomStyles::darker_background(if indx & 1 == 0 { 0.9 } else { 0.7 }));
+
+        entry_list = entry_list.push(entry);
+    }
+
+    let section = column![title, input_row, entry_list]
+        .spacing(10)
+        .padding(10);
+
+    let section = container(section)
+        .width(iced::Length::Fill)
+        .style(CustomStyles::darker_background(0.6));
+
+    container(section)
+        .style(CustomStyles::container_border(false))
+        .padding(1.0)
+        .into()
+}
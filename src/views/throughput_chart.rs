@@ -0,0 +1,73 @@
+use std::collections::VecDeque;
+use iced::{mouse, widget::canvas::{self, Frame, Geometry, Path, Stroke, Text}, Color, Point, Rectangle, Renderer, Theme};
+
+use crate::{server::webpage_service::size_string, state::client_manager::THROUGHPUT_HISTORY};
+
+pub struct ThroughputChart<'a> {
+    samples: &'a VecDeque<usize>,
+    color: Color,
+    label: bool,
+}
+
+impl<'a> ThroughputChart<'a> {
+    pub fn new(samples: &'a VecDeque<usize>, color: Color) -> Self {
+        Self { samples, color, label: false }
+    }
+
+    pub fn label(mut self, label: bool) -> Self {
+        self.label = label;
+        self
+    }
+}
+
+impl<Message> canvas::Program<Message> for ThroughputChart<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let max = self.samples.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let step = bounds.width / (THROUGHPUT_HISTORY - 1) as f32;
+        let offset = (THROUGHPUT_HISTORY - self.samples.len()) as f32 * step;
+        let points = self.samples.iter()
+            .enumerate()
+            .map(|(indx, sample)| Point::new(
+                offset + indx as f32 * step,
+                bounds.height - *sample as f32 / max * (bounds.height - 1.0),
+            ))
+            .collect::<Vec<_>>();
+
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            let line = Path::new(|builder| {
+                builder.move_to(*first);
+                points.iter().skip(1).for_each(|point| builder.line_to(*point));
+            });
+            let area = Path::new(|builder| {
+                builder.move_to(Point::new(first.x, bounds.height));
+                points.iter().for_each(|point| builder.line_to(*point));
+                builder.line_to(Point::new(last.x, bounds.height));
+                builder.close();
+            });
+            frame.fill(&area, Color { a: 0.2, ..self.color });
+            frame.stroke(&line, Stroke::default().with_color(self.color).with_width(1.5));
+        }
+
+        if self.label {
+            frame.fill_text(Text {
+                content: format!("{}/s", size_string(max as usize)),
+                position: Point::new(4.0, 2.0),
+                color: theme.palette().text,
+                size: 10.into(),
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
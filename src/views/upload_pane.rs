@@ -1,9 +1,9 @@
 use iced::{alignment, widget::{self, button, checkbox, column, container, horizontal_rule, hover, row, text, tooltip, Space}};
-use crate::{server::webpage_service::size_string, state::{file_manager::{CompressingZip, FileInfo}, state::State, update::Message}, views::styles::CustomStyles};
+use crate::{server::webpage_service::size_string, state::{file_manager::{CompressingZip, FileInfo, SharedEntry}, state::State, update::Message}, views::styles::CustomStyles};
 
 use super::root_view::{H1_SIZE, H2_SIZE, P_SIZE};
 
-pub fn upload_pane(state: &State) -> iced::Element<Message> {
+pub fn upload_pane(state: &State) -> iced::Element<'_, Message> {
     let upload_files = text(state.language.upload_file())
         .size(H1_SIZE);
 
@@ -42,7 +42,23 @@ pub fn upload_pane(state: &State) -> iced::Element<Message> {
         .align_x(iced::alignment::Horizontal::Center)
         .width(iced::Length::FillPortion(1));
 
-    let checkbox_row = row![ignore_hidden_checkbox, use_gitignore_checkbox]
+    let restore_checkbox = checkbox(state.language.restore_shared(), state.restore_shared)
+        .on_toggle(Message::RestoreShared);
+
+    let restore_checkbox = tooltip(
+        restore_checkbox,
+        container(text(state.language.restore_shared_tooltip()).size(P_SIZE))
+            .padding(10)
+            .width(iced::Length::Fixed(200.0))
+            .style(container::rounded_box),
+        tooltip::Position::Bottom
+    );
+
+    let restore_checkbox = container(restore_checkbox)
+        .align_x(iced::alignment::Horizontal::Center)
+        .width(iced::Length::FillPortion(1));
+
+    let checkbox_row = row![ignore_hidden_checkbox, use_gitignore_checkbox, restore_checkbox]
         .spacing(5)
         .width(iced::Length::Fill)
         .align_y(iced::alignment::Vertical::Center);
@@ -85,12 +101,64 @@ pub fn upload_pane(state: &State) -> iced::Element<Message> {
     
     let file_path = state.file_manager.get_view();
     let zipping_files = state.file_manager.get_zip_compressing();
+    let missing_files = state.file_manager.get_missing();
 
-    if !file_path.is_empty() || !zipping_files.is_empty() {
+    if !file_path.is_empty() || !zipping_files.is_empty() || !missing_files.is_empty() {
         let uploaded_files = text(state.language.shared_files(file_path.len()))
             .size(H1_SIZE);
 
         let mut files_list = column![].spacing(1);
+        if !missing_files.is_empty() {
+            let missing_title = text(state.language.missing_files(missing_files.len()))
+                .size(H2_SIZE)
+                .style(text::danger)
+                .width(iced::Length::Fill);
+
+            let retry_button = button(state.language.retry())
+                .on_press(Message::RetryMissing)
+                .width(iced::Length::Shrink);
+
+            let mut missing_list = column![
+                row![missing_title, retry_button]
+                    .spacing(5)
+                    .align_y(iced::alignment::Vertical::Center)
+            ].spacing(5);
+
+            for (i, entry) in missing_files.iter().enumerate() {
+                let path = entry.path().to_string_lossy().to_string();
+                let path = match entry {
+                    SharedEntry::File(_) => path,
+                    SharedEntry::Zip { .. } => format!("{}.zip", path),
+                };
+                let path_text = tooltip(
+                    text(path).size(P_SIZE).style(text::danger),
+                    container(text(state.language.missing_file_tooltip()).size(P_SIZE))
+                        .padding(10)
+                        .width(iced::Length::Fixed(200.0))
+                        .style(container::rounded_box),
+                    tooltip::Position::Bottom
+                );
+                let path_text = container(path_text)
+                    .width(iced::Length::Fill);
+
+                let remove_button = button(text("X").size(P_SIZE))
+                    .on_press(Message::RemoveMissing(i))
+                    .style(button::danger);
+
+                missing_list = missing_list.push(row![path_text, remove_button]
+                    .spacing(5)
+                    .align_y(iced::alignment::Vertical::Center));
+            }
+
+            let missing_list = container(missing_list)
+                .padding(12)
+                .style(CustomStyles::darker_background(0.9));
+
+            files_list = files_list.push(container(missing_list)
+                .padding(1.0)
+                .style(CustomStyles::container_border(true)));
+        }
+
         for (color, (path, CompressingZip { num_files, progress, ..})) in zipping_files.iter().enumerate() {
             let text_file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("Unknown").to_string();
             let text_file_name = text!("{}.zip", text_file_name)
@@ -141,6 +209,8 @@ pub fn upload_pane(state: &State) -> iced::Element<Message> {
             files_list = files_list.push(col);
         } 
 
+        let editing_invite = state.editing_invite.as_ref().and_then(|token| state.invite_manager.get(token));
+
         for (color, (i, FileInfo{path, download_count, size, ..})) in file_path.iter().cloned().rev().enumerate() {
             let color = color + zipping_files.len();
             let text_file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("Unknown").to_string();
@@ -206,6 +276,26 @@ pub fn upload_pane(state: &State) -> iced::Element<Message> {
                 .width(iced::Length::Fill)
                 .align_y(iced::alignment::Vertical::Center);
             
+            let title_row = match &editing_invite {
+                Some(invite) => {
+                    let visible = invite.files.as_ref().is_none_or(|files| files.contains(&path));
+                    let path = path.clone();
+                    let visible_checkbox = checkbox("", visible)
+                        .on_toggle(move |_| Message::ToggleInviteFile(path.clone()))
+                        .size(20);
+                    let visible_checkbox = tooltip(
+                        visible_checkbox,
+                        container(text(state.language.visible_for(&invite.name)).size(P_SIZE))
+                            .padding(10)
+                            .style(container::rounded_box),
+                        tooltip::Position::Right
+                    );
+                    row![visible_checkbox, title_row]
+                        .align_y(iced::alignment::Vertical::Center)
+                },
+                None => row![title_row],
+            };
+
             let col = column![
                 title_row,
                 text_current_file
@@ -15,6 +15,16 @@ function scheduleContentUpdate() {
 updateContent();
 scheduleContentUpdate();
 
+const nameCookie = document.cookie.split('; ').find(cookie => cookie.startsWith('fileshare_name='));
+document.getElementById('nameInput').value = nameCookie ? decodeURIComponent(nameCookie.split('=')[1]) : '';
+
+document.getElementById('nameForm').addEventListener('submit', (event) => {
+    event.preventDefault();
+    const name = document.getElementById('nameInput').value.trim();
+    document.cookie = 'fileshare_name=' + encodeURIComponent(name) + '; path=/; max-age=31536000; samesite=lax';
+    updateContent();
+});
+
 function downloadButtonString() {
     if (downloadsActive) {
         return 'Downloading...(' + numDownloadsCompleted + ' / ' + numDownloads + ')'; 
@@ -24,7 +34,7 @@ function downloadButtonString() {
 }
 
 document.getElementById('downloadAll').addEventListener('click', async () => {
-    await fetch('/download-all');
+    await fetch(basePath + '/download-all' + location.search);
     const links = Array.from(document.querySelectorAll('a.link'));
     const button = document.getElementById('downloadAll');
     const originalText = button.textContent;
@@ -62,10 +72,12 @@ document.getElementById('downloadAll').addEventListener('click', async () => {
 
 
 async function downloadFile(link) {
-    const url = link.href.slice(0, -1) + '0';
+    const url = new URL(link.href);
+    url.pathname = url.pathname.slice(0, -1) + '0';
 
     let isMobile = /iPhone|iPad|iPod|Android/i.test(navigator.userAgent);
-    let isLocal = /(^127\.)|(^192\.168\.)|(^10\.)|(^172\.1[6-9]\.)|(^172\.2[0-9]\.)|(^172\.3[0-1]\.)|(^::1$)|(^[fF][cCdD])/i.test(location.hostname);
+    let hostname = location.hostname.replace(/^\[|\]$/g, '').replace(/^::ffff:/i, '');
+    let isLocal = /(^127\.)|(^192\.168\.)|(^10\.)|(^172\.1[6-9]\.)|(^172\.2[0-9]\.)|(^172\.3[0-1]\.)|(^169\.254\.)|(^::1$)|(^f[cd][0-9a-f]{2}:)|(^fe[89ab][0-9a-f]:)|(\.local$)/i.test(hostname);
 
     if (isMobile && !isLocal) { 
         try {
@@ -76,6 +88,9 @@ async function downloadFile(link) {
     } else {
         try {
             const response = await fetch(url);
+            if (!response.ok) {
+                throw new Error(`${response.status} ${response.statusText}`);
+            }
             const blob = await response.blob();
             const contentDisposition = response.headers.get('content-disposition');
             const fileName = getFileNameFromContentDisposition(contentDisposition);
@@ -106,7 +121,10 @@ function to_color(color) {
 async function updateContent() {
     let button = document.getElementById('downloadAll');
     try {
-        const response = await fetch('/update-content');
+        const response = await fetch(basePath + '/update-content' + location.search);
+        if (response.status === 429) {
+            return;
+        }
         const json = await response.json();
         let html = json.html;
         let size = json.size;